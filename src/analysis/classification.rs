@@ -0,0 +1,125 @@
+//! Triage state of each example, from whether it passed or failed in each run.
+
+use serde::Serialize;
+
+use crate::{
+    analysis::{example_platforms, outcomes},
+    Kind, Platform, Run,
+};
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(tag = "state", content = "commit")]
+pub enum Classification {
+    /// Passing in all runs
+    #[default]
+    Stable,
+    /// Passed and failed more than once
+    Flaky,
+    /// Failing in all runs since this commit, and passing in all runs before
+    BrokenSince(String),
+    /// Passing in all runs since this commit, and failing in all runs before
+    FixedIn(String),
+    /// Not in the oldest run, and passing in all runs since
+    NewExample,
+}
+
+impl Classification {
+    /// The state of an example is the one of its platforms that most needs attention
+    fn priority(&self) -> u8 {
+        match self {
+            Classification::BrokenSince(_) => 4,
+            Classification::Flaky => 3,
+            Classification::FixedIn(_) => 2,
+            Classification::NewExample => 1,
+            Classification::Stable => 0,
+        }
+    }
+}
+
+/// `runs` are ordered from the most recent to the oldest. Screenshot changes are not taken
+/// into account, see `analysis::flakiness` for that.
+pub fn classify(runs: &[Run], example: &str) -> Classification {
+    let classification = example_platforms(runs, example)
+        .into_iter()
+        .map(|platform| classify_platform(runs, example, platform))
+        .max_by_key(Classification::priority)
+        .unwrap_or_default();
+    let is_new = runs.len() > 1
+        && runs
+            .last()
+            .is_some_and(|run| !run.results.contains_key(example));
+    match classification {
+        Classification::Stable if is_new => Classification::NewExample,
+        classification => classification,
+    }
+}
+
+fn classify_platform(runs: &[Run], example: &str, platform: &Platform) -> Classification {
+    // runs where the example ran, with whether it failed
+    let failed = outcomes(runs, example, platform)
+        .into_iter()
+        .map(|(run, kind, _)| (run, *kind == Kind::Failures))
+        .collect::<Vec<_>>();
+    let Some(&(_, latest)) = failed.first() else {
+        return Classification::Stable;
+    };
+
+    // the most recent runs with the same result as the latest one
+    let streak = failed
+        .iter()
+        .take_while(|(_, failed)| *failed == latest)
+        .count();
+    let since = failed[streak - 1].0.commit.clone();
+    let before = &failed[streak..];
+    if before.iter().any(|(_, failed)| *failed == latest) {
+        Classification::Flaky
+    } else if latest {
+        Classification::BrokenSince(since)
+    } else if before.is_empty() {
+        Classification::Stable
+    } else {
+        Classification::FixedIn(since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_util::history;
+
+    use super::*;
+
+    #[test]
+    fn classify_examples() {
+        use Kind::*;
+
+        let classify = |results: &[Option<Kind>]| classify(&history(results), "lighting");
+        assert_eq!(
+            classify(&[Some(Successes), Some(NoScreenshots)]),
+            Classification::Stable
+        );
+        assert_eq!(
+            classify(&[Some(Failures), Some(Failures), Some(Successes)]),
+            Classification::BrokenSince("1".to_string())
+        );
+        assert_eq!(
+            classify(&[Some(Failures), Some(Failures)]),
+            Classification::BrokenSince("1".to_string())
+        );
+        assert_eq!(
+            classify(&[Some(Successes), Some(Failures), Some(Failures)]),
+            Classification::FixedIn("0".to_string())
+        );
+        assert_eq!(
+            classify(&[Some(Failures), Some(Successes), Some(Failures)]),
+            Classification::Flaky
+        );
+        assert_eq!(
+            classify(&[Some(Successes), Some(Successes), None]),
+            Classification::NewExample
+        );
+        assert_eq!(
+            classify(&[Some(Failures), Some(Successes), None]),
+            Classification::BrokenSince("0".to_string())
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Commit ranges where the result or the screenshot of an example changed, to start
+//! bisecting from.
+
+use serde::Serialize;
+
+use crate::{
+    analysis::{example_platforms, outcomes},
+    Kind, Platform, Run,
+};
+
+/// A change of an example on a platform, between the last run before it and the first run
+/// with it. Runs where the example didn't run on the platform are skipped.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StateChange {
+    pub platform: String,
+    /// Result before and after the change, the same when only the screenshot changed
+    pub from: Kind,
+    pub to: Kind,
+    pub screenshot_changed: bool,
+    /// Commit of the last run with the previous state
+    pub good: String,
+    /// Commit of the first run with the new state
+    pub bad: String,
+}
+
+/// For each platform, when the latest result of the example started, and the latest
+/// screenshot change.
+///
+/// `runs` are ordered from the most recent to the oldest.
+pub fn find_state_changes(runs: &[Run], example: &str) -> Vec<StateChange> {
+    example_platforms(runs, example)
+        .into_iter()
+        .flat_map(|platform| find_platform_changes(runs, example, platform))
+        .collect()
+}
+
+fn find_platform_changes(runs: &[Run], example: &str, platform: &Platform) -> Vec<StateChange> {
+    let outcomes = outcomes(runs, example, platform);
+    // between the run at `index` and the previous one
+    let change = |index: usize, screenshot_changed: bool| {
+        let (bad, to, _) = outcomes[index];
+        let (good, from, _) = outcomes[index + 1];
+        StateChange {
+            platform: platform.to_string(),
+            from: from.clone(),
+            to: to.clone(),
+            screenshot_changed,
+            good: good.commit.clone(),
+            bad: bad.commit.clone(),
+        }
+    };
+
+    let mut changes = vec![];
+    let Some((_, latest, _)) = outcomes.first() else {
+        return changes;
+    };
+    let streak = outcomes
+        .iter()
+        .take_while(|(_, kind, _)| kind == latest)
+        .count();
+    if streak < outcomes.len() {
+        changes.push(change(streak - 1, false));
+    }
+    // the oldest run has nothing to compare its screenshot with
+    if let Some(bad) = outcomes[..outcomes.len() - 1]
+        .iter()
+        .position(|(_, _, changed)| *changed)
+    {
+        changes.push(change(bad, true));
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::test_util::{self, with_screenshot},
+        screenshot::ScreenshotState,
+    };
+
+    use super::*;
+
+    fn run(commit: &str, kind: Kind, screenshot: ScreenshotState) -> Run {
+        let run = test_util::run(commit, &[("lighting", Platform::Linux, kind)]);
+        with_screenshot(run, "lighting", Platform::Linux, screenshot)
+    }
+
+    #[test]
+    fn find_ranges() {
+        use ScreenshotState::*;
+
+        let runs = [
+            run("e", Kind::Failures, Similar),
+            run("d", Kind::Failures, Similar),
+            run("c", Kind::Successes, Changed),
+            run("b", Kind::Successes, Similar),
+            run("a", Kind::Successes, Changed),
+        ];
+        assert_eq!(
+            find_state_changes(&runs, "lighting"),
+            [
+                StateChange {
+                    platform: "Linux".to_string(),
+                    from: Kind::Successes,
+                    to: Kind::Failures,
+                    screenshot_changed: false,
+                    good: "c".to_string(),
+                    bad: "d".to_string(),
+                },
+                StateChange {
+                    platform: "Linux".to_string(),
+                    from: Kind::Successes,
+                    to: Kind::Successes,
+                    screenshot_changed: true,
+                    good: "b".to_string(),
+                    bad: "c".to_string(),
+                },
+            ]
+        );
+        assert!(find_state_changes(&runs[3..], "lighting").is_empty());
+    }
+}
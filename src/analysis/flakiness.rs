@@ -0,0 +1,91 @@
+//! How often the result of an example changes from one run to the next.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    analysis::{example_platforms, outcomes},
+    Platform, Run,
+};
+
+/// Weight of a change compared to the one in the following, more recent, run
+const RECENCY_WEIGHT: f32 = 0.9;
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Flakiness {
+    /// Highest score of all platforms, between 0 (never changes) and 1 (changes every run)
+    pub score: f32,
+    pub platforms: BTreeMap<String, f32>,
+}
+
+/// Scores an example on each platform with the weighted fraction of consecutive runs where
+/// its result changed or its screenshot was reported as changed.
+///
+/// `runs` are ordered from the most recent to the oldest, each change weighting
+/// `RECENCY_WEIGHT` times the one after it. Runs where the example didn't run are skipped.
+pub fn flakiness(runs: &[Run], example: &str) -> Flakiness {
+    let platforms = example_platforms(runs, example)
+        .into_iter()
+        .map(|platform| {
+            (
+                platform.to_string(),
+                platform_score(runs, example, platform),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+    Flakiness {
+        score: platforms.values().copied().fold(0.0, f32::max),
+        platforms,
+    }
+}
+
+fn platform_score(runs: &[Run], example: &str, platform: &Platform) -> f32 {
+    let outcomes = outcomes(runs, example, platform);
+
+    let (mut changes, mut total, mut weight) = (0.0, 0.0, 1.0);
+    for pair in outcomes.windows(2) {
+        let ((_, kind, changed), (_, previous, _)) = (pair[0], pair[1]);
+        if kind != previous || changed {
+            changes += weight;
+        }
+        total += weight;
+        weight *= RECENCY_WEIGHT;
+    }
+    if total == 0.0 {
+        0.0
+    } else {
+        changes / total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analysis::test_util::history, Kind};
+
+    use super::*;
+
+    fn runs(kinds: &[Kind]) -> Vec<Run> {
+        history(&kinds.iter().cloned().map(Some).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn score_changes() {
+        use Kind::*;
+
+        let stable = flakiness(&runs(&[Failures, Failures, Failures]), "lighting");
+        assert_eq!(stable.score, 0.0);
+        assert_eq!(stable.platforms["Linux"], 0.0);
+
+        let alternating = flakiness(&runs(&[Successes, Failures, Successes]), "lighting");
+        assert_eq!(alternating.score, 1.0);
+
+        // a recent change weighs more than an old one
+        let recent = flakiness(&runs(&[Failures, Successes, Successes]), "lighting");
+        let old = flakiness(&runs(&[Successes, Successes, Failures]), "lighting");
+        assert!(recent.score > 0.5 && old.score < 0.5);
+
+        assert_eq!(flakiness(&runs(&[Successes]), "lighting").score, 0.0);
+        assert_eq!(flakiness(&[], "lighting"), Flakiness::default());
+    }
+}
@@ -0,0 +1,217 @@
+//! What went wrong in the log of a failed example.
+
+use crate::ansi;
+
+/// Maximum length of a signature, in characters
+const MAX_SIGNATURE_LENGTH: usize = 200;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct LogAnalysis {
+    /// Message of the first panic
+    pub panic: Option<String>,
+    /// `<file>:<line>:<column>` of the first panic
+    pub location: Option<String>,
+    /// Validation errors reported by wgpu, with their context
+    pub wgpu_errors: Vec<String>,
+    /// Messages logged at the `ERROR` level
+    pub errors: Vec<String>,
+}
+
+impl LogAnalysis {
+    /// The most specific description of the failure: the first wgpu validation error, the
+    /// panic, or the first error
+    pub fn message(&self) -> Option<String> {
+        if let Some(error) = self.wgpu_errors.first() {
+            return Some(error.clone());
+        }
+        match (&self.panic, &self.location) {
+            (Some(panic), Some(location)) => return Some(format!("{} at {}", panic, location)),
+            (Some(panic), None) => return Some(panic.clone()),
+            _ => {}
+        }
+        self.errors.first().cloned()
+    }
+
+    /// The message without what changes between occurrences of the same failure, like
+    /// numbers, ids and line numbers, to group failures.
+    pub fn signature(&self) -> Option<String> {
+        let message = if let Some(error) = self.wgpu_errors.first() {
+            error.clone()
+        } else if let Some(panic) = &self.panic {
+            match self
+                .location
+                .as_ref()
+                .and_then(|location| location.rsplitn(3, ':').last())
+            {
+                Some(file) => format!("{} at {}", panic, file),
+                None => panic.clone(),
+            }
+        } else {
+            self.errors.first()?.clone()
+        };
+        Some(normalize(&message))
+    }
+}
+
+/// Panics are logged as `thread '<name>' panicked at <location>:` followed by the message on
+/// the next line, or on the same line in older Rust versions.
+pub fn analyze(log: &str) -> LogAnalysis {
+    let log = ansi::strip(log);
+    let lines = log.lines().map(str::trim).collect::<Vec<_>>();
+    let mut analysis = LogAnalysis::default();
+    for (index, line) in lines.iter().enumerate() {
+        if let Some((_, panic)) = line.split_once("panicked at ") {
+            if analysis.panic.is_none() {
+                let (message, location) = parse_panic(panic, &lines[index + 1..]);
+                analysis.panic = message;
+                analysis.location = location;
+            }
+        } else if line.contains("Validation Error") {
+            analysis
+                .wgpu_errors
+                .push(validation_error(&lines[index + 1..]));
+        } else if let Some((_, error)) = line.split_once(" ERROR ") {
+            let error = error.trim();
+            if !error.is_empty() {
+                analysis.errors.push(error.to_string());
+            }
+        }
+    }
+    analysis
+}
+
+fn parse_panic(panic: &str, following: &[&str]) -> (Option<String>, Option<String>) {
+    // before Rust 1.73: panicked at '<message>', <location>
+    if let Some(quoted) = panic.strip_prefix('\'') {
+        return match quoted.rsplit_once("', ") {
+            Some((message, location)) => (
+                Some(message.to_string()),
+                Some(location.trim_end_matches(':').to_string()),
+            ),
+            None => (Some(quoted.trim_end_matches('\'').to_string()), None),
+        };
+    }
+    match panic.split_once(": ") {
+        Some((location, message)) => (Some(message.to_string()), Some(location.to_string())),
+        None => (
+            following
+                .iter()
+                .find(|line| !line.is_empty())
+                .map(|line| line.to_string()),
+            Some(panic.trim_end_matches(':').to_string()),
+        ),
+    }
+}
+
+/// The `Caused by:` block after a validation error, without notes like labels
+fn validation_error(following: &[&str]) -> String {
+    let causes = following
+        .iter()
+        .skip_while(|line| line.is_empty() || **line == "Caused by:")
+        .take_while(|line| !line.is_empty())
+        .filter(|line| !line.starts_with("note:"))
+        .copied()
+        .collect::<Vec<_>>();
+    if causes.is_empty() {
+        "Validation Error".to_string()
+    } else {
+        causes.join(": ")
+    }
+}
+
+/// Replaces numbers with `N`, and collapses whitespace. Letters after a number are kept,
+/// `3d` becoming `Nd`, unless the number is hexadecimal with a `0x` prefix.
+fn normalize(message: &str) -> String {
+    let mut signature = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let hexadecimal = c == '0' && chars.next_if_eq(&'x').is_some();
+            while chars
+                .next_if(|c| c.is_ascii_digit() || (hexadecimal && c.is_ascii_hexdigit()))
+                .is_some()
+            {}
+            signature.push('N');
+        } else if c.is_whitespace() {
+            if !signature.ends_with(' ') {
+                signature.push(' ');
+            }
+        } else {
+            signature.push(c);
+        }
+    }
+    signature
+        .trim()
+        .chars()
+        .take(MAX_SIGNATURE_LENGTH)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analyze_panic() {
+        let log = "\u{1b}[2m2025-01-01T12:00:00Z\u{1b}[0m \u{1b}[31mERROR\u{1b}[0m bevy_asset: \
+            Path not found: assets/models/42.gltf\n\
+            thread 'main' panicked at crates/bevy_ecs/src/world/mod.rs:1234:9:\n\
+            Entity 12v3 does not exist\n\
+            note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n";
+        let analysis = analyze(log);
+        assert_eq!(
+            analysis.panic.as_deref(),
+            Some("Entity 12v3 does not exist")
+        );
+        assert_eq!(
+            analysis.location.as_deref(),
+            Some("crates/bevy_ecs/src/world/mod.rs:1234:9")
+        );
+        assert_eq!(
+            analysis.errors,
+            ["bevy_asset: Path not found: assets/models/42.gltf"]
+        );
+        assert_eq!(
+            analysis.signature().as_deref(),
+            Some("Entity NvN does not exist at crates/bevy_ecs/src/world/mod.rs")
+        );
+
+        let old = analyze("thread 'main' panicked at 'index out of bounds', src/main.rs:3:5");
+        assert_eq!(old.panic.as_deref(), Some("index out of bounds"));
+        assert_eq!(old.location.as_deref(), Some("src/main.rs:3:5"));
+
+        let names = analyze(
+            "thread 'main' panicked at examples/3d/bloom_3d.rs:12:5:\n\
+            Could not load 2d/sprite.png: handle 0x1f2e, 3dface",
+        );
+        assert_eq!(
+            names.signature().as_deref(),
+            Some("Could not load Nd/sprite.png: handle N, Ndface at examples/Nd/bloom_Nd.rs")
+        );
+
+        assert_eq!(analyze("all good\n"), LogAnalysis::default());
+        assert_eq!(analyze("all good\n").signature(), None);
+    }
+
+    #[test]
+    fn analyze_wgpu_error() {
+        let log = "thread 'Compute Task Pool (1)' panicked at wgpu-0.19.1/src/backend/wgpu_core.rs:3006:5:\n\
+            wgpu error: Validation Error\n\
+            \n\
+            Caused by:\n    \
+                In Device::create_render_pipeline\n      \
+                  note: label = `pbr_prepass_pipeline`\n    \
+                Error matching ShaderStages(VERTEX) shader requirements against the pipeline\n\
+            \n";
+        let analysis = analyze(log);
+        assert_eq!(
+            analysis.panic.as_deref(),
+            Some("wgpu error: Validation Error")
+        );
+        assert_eq!(
+            analysis.wgpu_errors,
+            ["In Device::create_render_pipeline: Error matching ShaderStages(VERTEX) shader requirements against the pipeline"]
+        );
+        assert_eq!(analysis.message(), analysis.signature());
+    }
+}
@@ -0,0 +1,40 @@
+use std::collections::BTreeSet;
+
+use crate::{screenshot::ScreenshotState, Kind, Platform, Run};
+
+pub mod classification;
+pub mod first_bad_commit;
+pub mod flakiness;
+pub mod log_analysis;
+pub mod run_summary;
+pub mod slowdown;
+#[cfg(test)]
+pub mod test_util;
+
+/// Platforms an example ran on in any of the runs, in display order
+pub fn example_platforms<'a>(runs: &'a [Run], example: &str) -> BTreeSet<&'a Platform> {
+    runs.iter()
+        .filter_map(|run| run.results.get(example))
+        .flat_map(|platforms| platforms.keys())
+        .collect()
+}
+
+/// Each run where an example ran on a platform, with its result and whether its screenshot
+/// was reported as changed, in the same order as `runs`
+pub fn outcomes<'a>(
+    runs: &'a [Run],
+    example: &str,
+    platform: &Platform,
+) -> Vec<(&'a Run, &'a Kind, bool)> {
+    runs.iter()
+        .filter_map(|run| {
+            let kind = run.results.get(example)?.get(platform)?;
+            let changed = run
+                .screenshots
+                .get(example)
+                .and_then(|screenshots| screenshots.get(platform))
+                .is_some_and(|(_, state, _)| *state == ScreenshotState::Changed);
+            Some((run, kind, changed))
+        })
+        .collect()
+}
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{screenshot::ScreenshotState, Kind, Run};
+
+/// Number of examples in each state for a platform.
+#[derive(Debug, Serialize, Default, PartialEq)]
+pub struct Totals {
+    pub successes: usize,
+    pub failures: usize,
+    pub no_screenshots: usize,
+    /// Successes whose screenshot changed
+    pub changed: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ExamplePlatform {
+    pub example: String,
+    pub platform: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ChangedScreenshot {
+    pub example: String,
+    pub platform: String,
+    pub screenshot: String,
+    pub snapshot_url: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Failure {
+    pub example: String,
+    pub platform: String,
+    pub log: Option<String>,
+}
+
+/// What happened in a run, and what changed since the previous one.
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub id: String,
+    pub date: String,
+    pub commit: String,
+    /// Id of the previous run, if there is one
+    pub previous: Option<String>,
+    pub totals: BTreeMap<String, Totals>,
+    pub changed: Vec<ChangedScreenshot>,
+    pub failures: Vec<Failure>,
+    /// Failing in this run, but not in the previous one
+    pub newly_failing: Vec<ExamplePlatform>,
+    /// Failing in the previous run, but not in this one
+    pub fixed: Vec<ExamplePlatform>,
+}
+
+/// Summarizes all runs, ordered from the most recent to the oldest.
+pub fn summarize_all(runs: &[Run]) -> Vec<RunSummary> {
+    runs.iter()
+        .enumerate()
+        .map(|(i, run)| summarize(run, runs.get(i + 1)))
+        .collect()
+}
+
+/// Examples that didn't run on a platform in `previous` are neither newly failing nor fixed.
+pub fn summarize(run: &Run, previous: Option<&Run>) -> RunSummary {
+    let mut summary = RunSummary {
+        id: run.id.clone(),
+        date: run.date.clone(),
+        commit: run.commit.clone(),
+        previous: previous.map(|previous| previous.id.clone()),
+        totals: BTreeMap::new(),
+        changed: vec![],
+        failures: vec![],
+        newly_failing: vec![],
+        fixed: vec![],
+    };
+
+    let mut results = run
+        .results
+        .iter()
+        .flat_map(|(example, platforms)| {
+            platforms
+                .iter()
+                .map(move |(platform, kind)| (example, platform, kind))
+        })
+        .collect::<Vec<_>>();
+    results.sort_by_key(|(example, platform, _)| (example.to_string(), platform.to_string()));
+
+    for (example, platform, kind) in results {
+        let totals = summary.totals.entry(platform.to_string()).or_default();
+        let screenshot = run
+            .screenshots
+            .get(example)
+            .and_then(|screenshots| screenshots.get(platform));
+        match kind {
+            Kind::Successes => {
+                totals.successes += 1;
+                if let Some((screenshot, ScreenshotState::Changed, snapshot_url)) = screenshot {
+                    totals.changed += 1;
+                    summary.changed.push(ChangedScreenshot {
+                        example: example.clone(),
+                        platform: platform.to_string(),
+                        screenshot: screenshot.0.clone(),
+                        snapshot_url: snapshot_url.0.clone(),
+                    });
+                }
+            }
+            Kind::Failures => {
+                totals.failures += 1;
+                summary.failures.push(Failure {
+                    example: example.clone(),
+                    platform: platform.to_string(),
+                    log: run
+                        .logs
+                        .get(example)
+                        .and_then(|logs| logs.get(&platform.to_string()))
+                        .cloned(),
+                });
+            }
+            Kind::NoScreenshots => totals.no_screenshots += 1,
+        }
+
+        let Some(previous_kind) = previous
+            .and_then(|previous| previous.results.get(example))
+            .and_then(|platforms| platforms.get(platform))
+        else {
+            continue;
+        };
+        let entry = || ExamplePlatform {
+            example: example.clone(),
+            platform: platform.to_string(),
+        };
+        match (previous_kind, kind) {
+            (Kind::Failures, Kind::Failures) => {}
+            (_, Kind::Failures) => summary.newly_failing.push(entry()),
+            (Kind::Failures, _) => summary.fixed.push(entry()),
+            _ => {}
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analysis::test_util::run, Platform};
+
+    use super::*;
+
+    #[test]
+    fn compare_with_previous_run() {
+        let previous = run(
+            "a",
+            &[
+                ("lighting", Platform::Linux, Kind::Successes),
+                ("shadows", Platform::Linux, Kind::Failures),
+                ("sprite", Platform::Linux, Kind::Failures),
+            ],
+        );
+        let current = run(
+            "b",
+            &[
+                ("lighting", Platform::Linux, Kind::Failures),
+                ("shadows", Platform::Linux, Kind::NoScreenshots),
+                ("sprite", Platform::Linux, Kind::Failures),
+                ("button", Platform::Linux, Kind::Failures),
+                ("lighting", Platform::Windows, Kind::Successes),
+            ],
+        );
+
+        let summary = summarize(&current, Some(&previous));
+        assert_eq!(summary.previous.as_deref(), Some("a"));
+        assert_eq!(
+            summary.totals["Linux"],
+            Totals {
+                successes: 0,
+                failures: 3,
+                no_screenshots: 1,
+                changed: 0,
+            }
+        );
+        assert_eq!(summary.totals["Windows"].successes, 1);
+        assert_eq!(
+            summary.newly_failing,
+            [ExamplePlatform {
+                example: "lighting".to_string(),
+                platform: "Linux".to_string(),
+            }]
+        );
+        assert_eq!(
+            summary.fixed,
+            [ExamplePlatform {
+                example: "shadows".to_string(),
+                platform: "Linux".to_string(),
+            }]
+        );
+        assert_eq!(summary.failures.len(), 3);
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::{Platform, Run};
+
+/// Minimum number of previous durations needed before comparing against them
+const MIN_SAMPLES: usize = 3;
+
+/// An example that started running slower than usual on a platform.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Slowdown {
+    pub example: String,
+    pub platform: String,
+    /// Run where the slowdown started
+    pub date: String,
+    pub commit: String,
+    pub duration: f32,
+    /// Median duration of the previous runs
+    pub baseline: f32,
+    /// `duration / baseline`
+    pub factor: f32,
+}
+
+/// Flags examples whose duration exceeds the median of the previous `window` runs by
+/// more than `factor`.
+///
+/// `runs` are ordered from the most recent to the oldest, and so are the results.
+/// Consecutive slow runs are reported once, at the run where they started.
+pub fn find_slowdowns(runs: &[Run], window: usize, factor: f32) -> Vec<Slowdown> {
+    if window == 0 {
+        return vec![];
+    }
+    let mut series = runs
+        .iter()
+        .flat_map(|run| run.durations.iter())
+        .flat_map(|(example, platforms)| platforms.keys().map(move |platform| (example, platform)))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    series.sort_by_key(|(example, platform)| (example.to_string(), platform.to_string()));
+
+    let mut slowdowns = vec![];
+    for (example, platform) in series {
+        slowdowns.extend(find_slowdowns_for(runs, example, platform, window, factor));
+    }
+    slowdowns.sort_by(|a, b| b.date.cmp(&a.date).then(b.factor.total_cmp(&a.factor)));
+    slowdowns
+}
+
+fn find_slowdowns_for(
+    runs: &[Run],
+    example: &str,
+    platform: &Platform,
+    window: usize,
+    factor: f32,
+) -> Vec<Slowdown> {
+    let mut slowdowns = vec![];
+    let mut previous = Vec::<f32>::new();
+    let mut slow = false;
+    for run in runs.iter().rev() {
+        let Some(duration) = run
+            .durations
+            .get(example)
+            .and_then(|durations| durations.get(platform))
+            .copied()
+        else {
+            continue;
+        };
+        let recent = &previous[previous.len().saturating_sub(window)..];
+        let baseline = median(recent).filter(|_| recent.len() >= MIN_SAMPLES.min(window));
+        if let Some(baseline) = baseline {
+            let is_slow = baseline > 0.0 && duration > baseline * factor;
+            if is_slow && !slow {
+                slowdowns.push(Slowdown {
+                    example: example.to_string(),
+                    platform: platform.to_string(),
+                    date: run.date.clone(),
+                    commit: run.commit.clone(),
+                    duration,
+                    baseline,
+                    factor: duration / baseline,
+                });
+            }
+            slow = is_slow;
+        }
+        previous.push(duration);
+    }
+    slowdowns
+}
+
+fn median(values: &[f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut values = values.to_vec();
+    values.sort_by(f32::total_cmp);
+    let middle = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_util::{self, with_duration};
+
+    use super::*;
+
+    fn run(commit: &str, duration: f32) -> Run {
+        with_duration(
+            test_util::run(commit, &[]),
+            "lighting",
+            Platform::Linux,
+            duration,
+        )
+    }
+
+    #[test]
+    fn detect_slowdown() {
+        // most recent first
+        let runs = [
+            run("07", 21.0),
+            run("06", 20.0),
+            run("05", 21.0),
+            run("04", 10.0),
+            run("03", 11.0),
+            run("02", 10.0),
+            run("01", 9.0),
+        ];
+        let slowdowns = find_slowdowns(&runs, 5, 1.5);
+        assert_eq!(slowdowns.len(), 1);
+        assert_eq!(slowdowns[0].commit, "05");
+        assert_eq!(slowdowns[0].baseline, 10.0);
+        assert_eq!(slowdowns[0].factor, 2.1);
+    }
+
+    #[test]
+    fn not_enough_history() {
+        let runs = [run("02", 30.0), run("01", 10.0)];
+        assert!(find_slowdowns(&runs, 5, 1.5).is_empty());
+        assert!(find_slowdowns(&runs, 0, 1.5).is_empty());
+    }
+}
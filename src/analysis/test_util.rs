@@ -0,0 +1,75 @@
+//! Runs built from a few results, for tests.
+
+use crate::{screenshot::ScreenshotState, ImageUrl, Kind, Platform, Run, SnapshotViewerUrl};
+
+/// A run of `commit` with the given results. The id is the commit, and the date sorts like it.
+pub fn run(commit: &str, results: &[(&str, Platform, Kind)]) -> Run {
+    let mut run = Run {
+        id: commit.to_string(),
+        date: format!("2024-01-01 {}", commit),
+        commit: commit.to_string(),
+        ..Default::default()
+    };
+    for (example, platform, kind) in results {
+        run.results
+            .entry(example.to_string())
+            .or_default()
+            .insert(platform.clone(), kind.clone());
+    }
+    run
+}
+
+/// One run per result of `lighting` on Linux, the commit being the index of the run. `None`
+/// is a run without the example.
+pub fn history(results: &[Option<Kind>]) -> Vec<Run> {
+    results
+        .iter()
+        .enumerate()
+        .map(|(index, kind)| {
+            let results = kind
+                .iter()
+                .map(|kind| ("lighting", Platform::Linux, kind.clone()))
+                .collect::<Vec<_>>();
+            run(&index.to_string(), &results)
+        })
+        .collect()
+}
+
+/// The screenshot is at `screenshots/<run>/<platform>/<example>.png`, and compared at
+/// `https://pixel-eagle.com/compare/<run>`.
+pub fn with_screenshot(
+    mut run: Run,
+    example: &str,
+    platform: Platform,
+    state: ScreenshotState,
+) -> Run {
+    let screenshot = (
+        ImageUrl(format!(
+            "screenshots/{}/{}/{}.png",
+            run.id, platform, example
+        )),
+        state,
+        SnapshotViewerUrl(format!("https://pixel-eagle.com/compare/{}", run.id)),
+    );
+    run.screenshots
+        .entry(example.to_string())
+        .or_default()
+        .insert(platform, screenshot);
+    run
+}
+
+pub fn with_duration(mut run: Run, example: &str, platform: Platform, duration: f32) -> Run {
+    run.durations
+        .entry(example.to_string())
+        .or_default()
+        .insert(platform, duration);
+    run
+}
+
+pub fn with_log(mut run: Run, example: &str, platform: Platform, log: &str) -> Run {
+    run.logs
+        .entry(example.to_string())
+        .or_default()
+        .insert(platform.to_string(), log.to_string());
+    run
+}
@@ -0,0 +1,251 @@
+//! ANSI escape sequences in logs, used by terminals for colors and cursor movements.
+//!
+//! Colors and styles can be converted to HTML spans with `ansi-*` classes, other sequences
+//! are removed. Classes are used instead of inline styles as Bootstrap popovers strip them.
+
+/// Default colors of xterm for the 16 basic colors, to find the closest one to other colors
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    /// Select Graphic Rendition, `ESC [ <parameters> m`
+    Sgr(Vec<u16>),
+}
+
+/// Removes all escape sequences.
+pub fn strip(text: &str) -> String {
+    tokens(text)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            Token::Sgr(_) => None,
+        })
+        .collect()
+}
+
+/// Escapes `text` for HTML, with colors and styles converted to spans.
+pub fn to_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut style = Style::default();
+    let mut in_span = false;
+    for token in tokens(text) {
+        match token {
+            Token::Text(text) => {
+                if !in_span && style != Style::default() {
+                    html.push_str(&format!("<span class=\"{}\">", style.classes()));
+                    in_span = true;
+                }
+                escape_into(&mut html, text);
+            }
+            Token::Sgr(parameters) => {
+                let previous = style.clone();
+                style.apply(&parameters);
+                if in_span && style != previous {
+                    html.push_str("</span>");
+                    in_span = false;
+                }
+            }
+        }
+    }
+    if in_span {
+        html.push_str("</span>");
+    }
+    html
+}
+
+fn tokens(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('\u{1b}') {
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        let sequence = &rest[start + 1..];
+        let length = match sequence.chars().next() {
+            // Control Sequence Introducer: parameters, then a final byte between `@` and `~`
+            Some('[') => match sequence[1..].find(|c: char| ('@'..='~').contains(&c)) {
+                Some(end) => {
+                    if sequence[end + 1..].starts_with('m') {
+                        tokens.push(Token::Sgr(parameters(&sequence[1..end + 1])));
+                    }
+                    end + 2
+                }
+                None => sequence.len(),
+            },
+            // Operating System Command, like window titles or links, ended by BEL or ST
+            Some(']') => match (sequence.find('\u{7}'), sequence.find("\u{1b}\\")) {
+                (Some(bel), Some(st)) if st < bel => st + 2,
+                (Some(bel), _) => bel + 1,
+                (None, Some(st)) => st + 2,
+                (None, None) => sequence.len(),
+            },
+            Some(c) => c.len_utf8(),
+            None => 0,
+        };
+        rest = &sequence[length..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    tokens
+}
+
+/// Parameters are separated by `;`, or `:` in the extended color syntax. Missing ones are 0.
+fn parameters(parameters: &str) -> Vec<u16> {
+    parameters
+        .split([';', ':'])
+        .map(|parameter| parameter.parse().unwrap_or(0))
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Style {
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    /// Index in the 16 basic colors
+    foreground: Option<u8>,
+    background: Option<u8>,
+}
+
+impl Style {
+    fn apply(&mut self, parameters: &[u16]) {
+        let mut parameters = parameters.iter().copied();
+        while let Some(parameter) = parameters.next() {
+            match parameter {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => (self.bold, self.dim) = (false, false),
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.foreground = Some((parameter - 30) as u8),
+                90..=97 => self.foreground = Some((parameter - 90 + 8) as u8),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some((parameter - 40) as u8),
+                100..=107 => self.background = Some((parameter - 100 + 8) as u8),
+                49 => self.background = None,
+                38 => self.foreground = extended_color(&mut parameters),
+                48 => self.background = extended_color(&mut parameters),
+                _ => {}
+            }
+        }
+    }
+
+    fn classes(&self) -> String {
+        let mut classes = vec![];
+        for (enabled, class) in [
+            (self.bold, "ansi-bold"),
+            (self.dim, "ansi-dim"),
+            (self.italic, "ansi-italic"),
+            (self.underline, "ansi-underline"),
+        ] {
+            if enabled {
+                classes.push(class.to_string());
+            }
+        }
+        if let Some(color) = self.foreground {
+            classes.push(format!("ansi-fg-{}", color));
+        }
+        if let Some(color) = self.background {
+            classes.push(format!("ansi-bg-{}", color));
+        }
+        classes.join(" ")
+    }
+}
+
+/// `5;<index>` for 256 colors or `2;<r>;<g>;<b>`, approximated by the closest basic color
+fn extended_color(parameters: &mut impl Iterator<Item = u16>) -> Option<u8> {
+    let mut next = || parameters.next().unwrap_or(0).min(255) as u8;
+    let (r, g, b) = match next() {
+        5 => match next() {
+            index @ 0..=15 => return Some(index),
+            index @ 16..=231 => {
+                let level = |value: u8| if value == 0 { 0 } else { value * 40 + 55 };
+                let index = index - 16;
+                (level(index / 36), level(index / 6 % 6), level(index % 6))
+            }
+            index => {
+                let grey = (index - 232) * 10 + 8;
+                (grey, grey, grey)
+            }
+        },
+        2 => (next(), next(), next()),
+        _ => return None,
+    };
+    let distance = |(pr, pg, pb): (u8, u8, u8)| {
+        [(r, pr), (g, pg), (b, pb)]
+            .into_iter()
+            .map(|(a, b)| (a as i32 - b as i32).pow(2))
+            .sum::<i32>()
+    };
+    (0..PALETTE.len() as u8).min_by_key(|index| distance(PALETTE[*index as usize]))
+}
+
+fn escape_into(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#x27;"),
+            c if c.is_control() && !matches!(c, '\t' | '\n') => {}
+            c => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\u{1b}[2m2025-01-01\u{1b}[0m \u{1b}[31mERROR\u{1b}[0m \u{1b}[90mwgpu\u{1b}[0m: \
+        <oops> \u{1b}]8;;https://bevy.org\u{7}link\u{1b}]8;;\u{7} \u{1b}[38;5;208mwarn\u{1b}[39m\u{1b}[2K";
+
+    #[test]
+    fn strip_sequences() {
+        assert_eq!(strip(LOG), "2025-01-01 ERROR wgpu: <oops> link warn");
+        assert_eq!(strip("no escape"), "no escape");
+        assert_eq!(strip("unterminated \u{1b}[31"), "unterminated ");
+    }
+
+    #[test]
+    fn convert_to_html() {
+        assert_eq!(
+            to_html(LOG),
+            "<span class=\"ansi-dim\">2025-01-01</span> <span class=\"ansi-fg-1\">ERROR</span> \
+            <span class=\"ansi-fg-8\">wgpu</span>: &lt;oops&gt; link \
+            <span class=\"ansi-fg-3\">warn</span>"
+        );
+        assert_eq!(
+            to_html("\u{1b}[1;32mok\u{1b}[22m!\u{1b}[0m"),
+            "<span class=\"ansi-bold ansi-fg-2\">ok</span><span class=\"ansi-fg-2\">!</span>"
+        );
+        assert_eq!(
+            to_html("\u{1b}[38;2;250;10;10mred"),
+            "<span class=\"ansi-fg-9\">red</span>"
+        );
+    }
+}
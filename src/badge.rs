@@ -0,0 +1,112 @@
+//! SVG badges with the state of the most recent run, to embed in READMEs and dashboards.
+//!
+//! - `badges/<platform>.svg`, with how many examples passed on the platform
+//! - `badges/examples/<category>/<name>.svg`, with how many platforms the example passed on
+
+use std::{fs, path::Path};
+
+use crate::{
+    analysis::run_summary::RunSummary,
+    error::ReportError,
+    xml::{escape, slug},
+    Example, Kind, Run,
+};
+
+const GREEN: &str = "#4c1";
+const RED: &str = "#e05d44";
+const GREY: &str = "#9f9f9f";
+
+/// Writes the badges for the most recent run in `output`.
+pub fn write_badges(
+    output: &Path,
+    latest: Option<(&Run, &RunSummary)>,
+    all_examples: &[Example],
+) -> Result<(), ReportError> {
+    let folder = output.join("badges");
+    fs::create_dir_all(&folder).map_err(|error| ReportError::io(&folder, error))?;
+
+    if let Some((_, summary)) = latest {
+        for (platform, totals) in &summary.totals {
+            let total = totals.successes + totals.no_screenshots + totals.failures;
+            let passing = total - totals.failures;
+            let color = if totals.failures == 0 { GREEN } else { RED };
+            write(
+                &folder.join(format!("{}.svg", slug(platform))),
+                &render(platform, &format!("{}/{} passing", passing, total), color),
+            )?;
+        }
+    }
+
+    for example in all_examples {
+        let results = latest.and_then(|(run, _)| run.results.get(&example.name));
+        let (message, color) = match results {
+            None => ("not run".to_string(), GREY),
+            Some(platforms) => {
+                let failures = platforms
+                    .values()
+                    .filter(|kind| **kind == Kind::Failures)
+                    .count();
+                if failures == 0 {
+                    ("passing".to_string(), GREEN)
+                } else {
+                    (
+                        format!("{}/{} passing", platforms.len() - failures, platforms.len()),
+                        RED,
+                    )
+                }
+            }
+        };
+        let folder = folder.join("examples").join(&example.category.0);
+        fs::create_dir_all(&folder).map_err(|error| ReportError::io(&folder, error))?;
+        write(
+            &folder.join(format!("{}.svg", example.name)),
+            &render(&example.name, &message, color),
+        )?;
+    }
+    Ok(())
+}
+
+/// A flat badge, in the style of shields.io
+fn render(label: &str, message: &str, color: &str) -> String {
+    // approximation of the width of Verdana 11px
+    let width = |text: &str| text.chars().count() * 7 + 10;
+    let (label_width, message_width) = (width(label), width(message));
+    let total = label_width + message_width;
+    let (label, message) = (escape(label), escape(message));
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total}" height="20" role="img" aria-label="{label}: {message}">
+<title>{label}: {message}</title>
+<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+<clipPath id="r"><rect width="{total}" height="20" rx="3" fill="#fff"/></clipPath>
+<g clip-path="url(#r)">
+<rect width="{label_width}" height="20" fill="#555"/>
+<rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/>
+<rect width="{total}" height="20" fill="url(#s)"/>
+</g>
+<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+<text x="{label_x}" y="14">{label}</text>
+<text x="{message_x}" y="14">{message}</text>
+</g>
+</svg>
+"##,
+        label_x = label_width / 2,
+        message_x = label_width + message_width / 2,
+    )
+}
+
+fn write(path: &Path, svg: &str) -> Result<(), ReportError> {
+    fs::write(path, svg).map_err(|error| ReportError::io(path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_badge() {
+        let svg = render("Linux", "312/318 passing", GREEN);
+        assert!(svg.contains("<title>Linux: 312/318 passing</title>"));
+        assert!(svg.contains(r#"width="160""#));
+        assert!(render("a<b", "ok", GREEN).contains("a&lt;b"));
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::ReportError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Use cached responses, fetch and store the missing ones
+    Normal,
+    /// Only use cached responses, never fetch
+    Offline,
+    /// Ignore cached responses, fetch and store them again
+    Refresh,
+}
+
+/// On-disk cache of screenshot provider responses that will never change.
+pub struct ResponseCache {
+    dir: PathBuf,
+    mode: CacheMode,
+}
+
+impl ResponseCache {
+    pub fn new(dir: &Path, mode: CacheMode) -> Self {
+        ResponseCache {
+            dir: dir.to_path_buf(),
+            mode,
+        }
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        if self.mode == CacheMode::Refresh {
+            return None;
+        }
+        fs::read_to_string(self.path(key)).ok()
+    }
+
+    pub fn store(&self, key: &str, response: &str) -> Result<(), ReportError> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| ReportError::io(parent, error))?;
+        }
+        fs::write(&path, response).map_err(|error| ReportError::io(&path, error))
+    }
+
+    /// Keys are `/` separated, each part is sanitized to stay inside the cache folder
+    fn path(&self, key: &str) -> PathBuf {
+        let mut path = self.dir.clone();
+        for part in key.split('/') {
+            path.push(
+                part.chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect::<String>(),
+            );
+        }
+        path.set_extension("json");
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_and_get() {
+        let dir = std::env::temp_dir().join(format!("example-report-cache-{}", std::process::id()));
+        let cache = ResponseCache::new(&dir, CacheMode::Normal);
+        assert_eq!(cache.get("pixeleagle/project/1-2"), None);
+        cache.store("pixeleagle/project/1-2", "{}").unwrap();
+        assert_eq!(cache.get("pixeleagle/project/1-2").as_deref(), Some("{}"));
+        assert!(dir.join("pixeleagle/project/1-2.json").exists());
+
+        assert_eq!(cache.path("../escape"), dir.join("__").join("escape.json"));
+
+        let refresh = ResponseCache::new(&dir, CacheMode::Refresh);
+        assert_eq!(refresh.get("pixeleagle/project/1-2"), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+//! Text diff of two runs, for the `diff` and `query` commands.
+
+use std::collections::BTreeSet;
+
+use crate::{screenshot::ScreenshotState, Kind, Platform, Run};
+
+/// One line per example and platform whose result or screenshot differs, prefixed with
+/// `-` when only in `from`, `+` when only in `to`, and `~` when in both.
+pub fn diff(from: &Run, to: &Run) -> Vec<String> {
+    let keys = [from, to]
+        .iter()
+        .flat_map(|run| run.results.iter())
+        .flat_map(|(example, platforms)| platforms.keys().map(move |platform| (example, platform)))
+        .collect::<BTreeSet<_>>();
+
+    let mut lines = vec![];
+    for (example, platform) in keys {
+        let line = match (
+            describe(from, example, platform),
+            describe(to, example, platform),
+        ) {
+            (Some(before), Some(after)) if before == after => continue,
+            (Some(before), Some(after)) => {
+                format!("~ {} / {}: {} -> {}", example, platform, before, after)
+            }
+            (Some(before), None) => format!("- {} / {}: {}", example, platform, before),
+            (None, Some(after)) => format!("+ {} / {}: {}", example, platform, after),
+            (None, None) => continue,
+        };
+        lines.push(line);
+    }
+    lines
+}
+
+/// Result of an example on a platform, like `success, screenshot changed`
+pub fn describe(run: &Run, example: &str, platform: &Platform) -> Option<String> {
+    let kind = run.results.get(example)?.get(platform)?;
+    let mut description = match kind {
+        Kind::Successes => "success",
+        Kind::Failures => "failure",
+        Kind::NoScreenshots => "no screenshot",
+    }
+    .to_string();
+    if let Some((_, state, _)) = run
+        .screenshots
+        .get(example)
+        .and_then(|screenshots| screenshots.get(platform))
+    {
+        description.push_str(match state {
+            ScreenshotState::Similar => ", screenshot similar",
+            ScreenshotState::Changed => ", screenshot changed",
+        });
+    }
+    Some(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_util::run;
+
+    use super::*;
+
+    #[test]
+    fn diff_runs() {
+        let from = run(
+            "a",
+            &[
+                ("lighting", Platform::Linux, Kind::Successes),
+                ("shadows", Platform::Linux, Kind::Successes),
+                ("sprite", Platform::Linux, Kind::Successes),
+            ],
+        );
+        let to = run(
+            "b",
+            &[
+                ("lighting", Platform::Linux, Kind::Failures),
+                ("shadows", Platform::Linux, Kind::Successes),
+                ("button", Platform::Windows, Kind::NoScreenshots),
+            ],
+        );
+        assert_eq!(
+            diff(&from, &to),
+            [
+                "+ button / Windows: no screenshot",
+                "~ lighting / Linux: success -> failure",
+                "- sprite / Linux: success",
+            ]
+        );
+        assert!(diff(&from, &from).is_empty());
+    }
+}
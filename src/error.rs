@@ -0,0 +1,116 @@
+use std::{fmt, io, path::PathBuf};
+
+use serde::Serialize;
+
+/// Everything that can go wrong while reading the results folder.
+#[derive(Debug)]
+pub enum ReportError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    /// Run folders are named `<date>-<commit>`
+    InvalidRunFolder(String),
+    InvalidDate {
+        value: String,
+        error: chrono::ParseError,
+    },
+    UnknownPlatform(String),
+    UnknownKind(String),
+    /// Lines in status files are `category/name - duration`
+    InvalidResultLine {
+        path: PathBuf,
+        line: String,
+    },
+    /// A JUnit or libtest report that can't be parsed
+    InvalidTestReport {
+        path: PathBuf,
+        message: String,
+    },
+    InvalidScreenshotName(String),
+    UnknownProvider(String),
+    /// Number of runs that `validate` couldn't read
+    InvalidRuns(usize),
+    /// A template can't be parsed or rendered
+    Template(String),
+    /// A screenshot provider failed to fetch or read screenshots
+    Provider {
+        provider: &'static str,
+        message: String,
+    },
+}
+
+impl ReportError {
+    pub fn io(path: impl Into<PathBuf>, error: io::Error) -> Self {
+        ReportError::Io {
+            path: path.into(),
+            error,
+        }
+    }
+
+    /// Tera errors only say which template failed, the cause is in their sources
+    pub fn template(error: tera::Error) -> Self {
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(&error);
+        while let Some(error) = source {
+            message.push_str(&format!(": {}", error));
+            source = error.source();
+        }
+        ReportError::Template(message)
+    }
+
+    pub fn provider(provider: &'static str, error: impl fmt::Display) -> Self {
+        ReportError::Provider {
+            provider,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportError::Io { path, error } => write!(f, "can't read {:?}: {}", path, error),
+            ReportError::InvalidRunFolder(name) => {
+                write!(f, "run folder {:?} is not named <date>-<commit>", name)
+            }
+            ReportError::InvalidDate { value, error } => {
+                write!(f, "invalid run date {:?}: {}", value, error)
+            }
+            ReportError::UnknownPlatform(platform) => write!(f, "unknown platform {:?}", platform),
+            ReportError::UnknownKind(kind) => write!(f, "unknown result kind {:?}", kind),
+            ReportError::InvalidResultLine { path, line } => {
+                write!(f, "invalid line {:?} in {:?}", line, path)
+            }
+            ReportError::InvalidTestReport { path, message } => {
+                write!(f, "invalid test report {:?}: {}", path, message)
+            }
+            ReportError::InvalidScreenshotName(name) => {
+                write!(f, "invalid screenshot name {:?}", name)
+            }
+            ReportError::UnknownProvider(name) => {
+                write!(f, "unknown screenshot provider {:?}", name)
+            }
+            ReportError::InvalidRuns(count) => write!(f, "{} runs can't be read", count),
+            ReportError::Template(message) => write!(f, "template error: {}", message),
+            ReportError::Provider { provider, message } => write!(f, "{}: {}", provider, message),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReportError::Io { error, .. } => Some(error),
+            ReportError::InvalidDate { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A run that was skipped because it couldn't be read, shown on the site.
+#[derive(Debug, Serialize)]
+pub struct IngestionProblem {
+    pub path: String,
+    pub error: String,
+}
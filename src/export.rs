@@ -0,0 +1,262 @@
+//! JSON export of the report, for tools that want the same data as the site.
+//!
+//! Two kinds of files are written in `data/`:
+//! - `report.json`, a [`Report`] with all examples and the list of runs
+//! - `runs/<id>.json`, a [`RunReport`] with all results of a run
+//!
+//! Every file has a `version` field set to [`SCHEMA_VERSION`]. It is increased when a
+//! field is removed or changes meaning, adding a field doesn't change it.
+//! Runs are ordered from the most recent to the oldest, results by example then platform.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use serde::Serialize;
+
+use crate::{
+    analysis::{classification::Classification, flakiness::Flakiness, log_analysis},
+    ansi,
+    error::ReportError,
+    screenshot::ScreenshotState,
+    Example, Kind, Run,
+};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// `data/report.json`
+#[derive(Debug, Serialize)]
+pub struct Report<'a> {
+    pub version: u32,
+    /// RFC 3339 date of the export
+    pub generated_at: String,
+    /// Desktop and web platforms, as displayed in the report
+    pub platforms: &'a [String],
+    /// Mobile devices, sorted
+    pub mobile_platforms: Vec<&'a String>,
+    pub examples: Vec<ReportExample<'a>>,
+    pub runs: Vec<RunEntry<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportExample<'a> {
+    pub category: &'a str,
+    pub name: &'a str,
+    /// Between 0 and 1, see `analysis::flakiness`
+    pub flakiness: &'a Flakiness,
+    pub classification: &'a Classification,
+    /// Duration in seconds for each platform, one per run, `null` when it didn't run
+    pub durations: &'a BTreeMap<String, Vec<Option<f32>>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunEntry<'a> {
+    /// Name of the run folder, `<date>-<commit>`
+    pub id: &'a str,
+    /// `YYYY-MM-DD HH:MM`
+    pub date: &'a str,
+    pub commit: &'a str,
+    /// Path to the [`RunReport`], relative to `data/`
+    pub file: String,
+}
+
+/// `data/runs/<id>.json`
+#[derive(Debug, Serialize)]
+pub struct RunReport<'a> {
+    pub version: u32,
+    pub id: &'a str,
+    pub date: &'a str,
+    pub commit: &'a str,
+    pub results: Vec<ResultEntry<'a>>,
+    /// Platforms whose screenshots couldn't be fetched, with the reason
+    pub screenshots_unavailable: BTreeMap<String, &'a str>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResultEntry<'a> {
+    /// `null` for examples that are not known from the status files
+    pub category: Option<&'a str>,
+    pub example: &'a str,
+    pub platform: String,
+    pub status: Status,
+    /// In seconds
+    pub duration: Option<f32>,
+    pub screenshot: Option<Screenshot<'a>>,
+    /// Normalized description of the failure, the same for the same failures
+    pub signature: Option<String>,
+    /// Without ANSI escape sequences
+    pub log: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Success,
+    Failure,
+    /// Ran successfully, but no screenshot was taken
+    NoScreenshot,
+}
+
+impl From<&Kind> for Status {
+    fn from(kind: &Kind) -> Self {
+        match kind {
+            Kind::Successes => Status::Success,
+            Kind::Failures => Status::Failure,
+            Kind::NoScreenshots => Status::NoScreenshot,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Screenshot<'a> {
+    /// Absolute, or relative to the site root for local screenshots
+    pub url: &'a str,
+    pub changed: bool,
+    pub snapshot_url: &'a str,
+}
+
+/// Writes `data/report.json` and one file per run in `output`.
+pub fn write_json(
+    output: &Path,
+    runs: &[Run],
+    all_examples: &[Example],
+    all_mobile_platforms: &HashSet<String>,
+    all_platforms: &[String],
+) -> Result<(), ReportError> {
+    let folder = output.join("data").join("runs");
+    fs::create_dir_all(&folder).map_err(|error| ReportError::io(&folder, error))?;
+
+    let mut mobile_platforms = all_mobile_platforms.iter().collect::<Vec<_>>();
+    mobile_platforms.sort();
+    let report = Report {
+        version: SCHEMA_VERSION,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        platforms: all_platforms,
+        mobile_platforms,
+        examples: all_examples
+            .iter()
+            .map(|example| ReportExample {
+                category: &example.category.0,
+                name: &example.name,
+                flakiness: &example.flakiness,
+                classification: &example.classification,
+                durations: &example.durations,
+            })
+            .collect(),
+        runs: runs
+            .iter()
+            .map(|run| RunEntry {
+                id: &run.id,
+                date: &run.date,
+                commit: &run.commit,
+                file: format!("runs/{}.json", run.id),
+            })
+            .collect(),
+    };
+    write(&output.join("data").join("report.json"), &report)?;
+
+    let categories = all_examples
+        .iter()
+        .map(|example| (example.name.as_str(), example.category.0.as_str()))
+        .collect::<HashMap<_, _>>();
+    for run in runs {
+        write(
+            &folder.join(format!("{}.json", run.id)),
+            &run_report(run, &categories),
+        )?;
+    }
+    Ok(())
+}
+
+fn run_report<'a>(run: &'a Run, categories: &HashMap<&str, &'a str>) -> RunReport<'a> {
+    let mut results = run
+        .results
+        .iter()
+        .flat_map(|(example, platforms)| {
+            platforms.iter().map(move |(platform, kind)| {
+                let log = run
+                    .logs
+                    .get(example)
+                    .and_then(|logs| logs.get(&platform.to_string()));
+                ResultEntry {
+                    category: categories.get(example.as_str()).copied(),
+                    example,
+                    platform: platform.to_string(),
+                    status: kind.into(),
+                    duration: run
+                        .durations
+                        .get(example)
+                        .and_then(|durations| durations.get(platform))
+                        .copied(),
+                    screenshot: run
+                        .screenshots
+                        .get(example)
+                        .and_then(|screenshots| screenshots.get(platform))
+                        .map(|(image, state, snapshot_url)| Screenshot {
+                            url: &image.0,
+                            changed: *state == ScreenshotState::Changed,
+                            snapshot_url: &snapshot_url.0,
+                        }),
+                    signature: log.and_then(|log| log_analysis::analyze(log).signature()),
+                    log: log.map(|log| ansi::strip(log)),
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    results.sort_by(|a, b| (a.example, &a.platform).cmp(&(b.example, &b.platform)));
+
+    RunReport {
+        version: SCHEMA_VERSION,
+        id: &run.id,
+        date: &run.date,
+        commit: &run.commit,
+        results,
+        screenshots_unavailable: run
+            .screenshots_unavailable
+            .iter()
+            .map(|(platform, reason)| (platform.to_string(), reason.as_str()))
+            .collect(),
+    }
+}
+
+fn write(path: &Path, value: &impl Serialize) -> Result<(), ReportError> {
+    let json = serde_json::to_string_pretty(value).expect("report can be serialized");
+    fs::write(path, json).map_err(|error| ReportError::io(path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        analysis::test_util::{run, with_screenshot},
+        Platform,
+    };
+
+    use super::*;
+
+    #[test]
+    fn run_results() {
+        let run = run(
+            "abc",
+            &[
+                ("lighting", Platform::Windows, Kind::Failures),
+                ("lighting", Platform::Linux, Kind::Successes),
+            ],
+        );
+        let run = with_screenshot(run, "lighting", Platform::Linux, ScreenshotState::Changed);
+        let categories = HashMap::from([("lighting", "3d")]);
+        let report = run_report(&run, &categories);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["version"], SCHEMA_VERSION);
+        assert_eq!(json["results"][0]["platform"], "Linux");
+        assert_eq!(json["results"][0]["category"], "3d");
+        assert_eq!(json["results"][0]["status"], "success");
+        assert_eq!(json["results"][0]["screenshot"]["changed"], true);
+        assert_eq!(json["results"][1]["status"], "failure");
+        assert_eq!(json["results"][1]["screenshot"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    sync::{Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::error::ReportError;
+
+/// Maximum delay between two attempts, whatever the backoff or `Retry-After` say
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// Requests in flight at the same time
+    pub max_in_flight: usize,
+    /// Minimum delay between the start of two requests
+    pub interval: Duration,
+    pub timeout: Duration,
+    /// Attempts after the first one for timeouts, 429 and 5xx responses
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each following one
+    pub backoff: Duration,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            max_in_flight: 4,
+            interval: Duration::ZERO,
+            timeout: Duration::from_secs(30),
+            retries: 3,
+            backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// HTTP client shared by all screenshot providers, limiting how many requests are
+/// in flight and how often they are sent, and retrying failed requests.
+pub struct HttpClient {
+    config: HttpConfig,
+    agent: ureq::Agent,
+    in_flight: Mutex<usize>,
+    released: Condvar,
+    next_request: Mutex<Instant>,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        HttpClient::new(HttpConfig::default())
+    }
+}
+
+impl HttpClient {
+    pub fn new(config: HttpConfig) -> Self {
+        HttpClient {
+            agent: ureq::AgentBuilder::new().timeout(config.timeout).build(),
+            config: HttpConfig {
+                max_in_flight: config.max_in_flight.max(1),
+                ..config
+            },
+            in_flight: Mutex::new(0),
+            released: Condvar::new(),
+            next_request: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Sends a GET request and returns the body of the response.
+    pub fn get(&self, provider: &'static str, url: &str) -> Result<String, ReportError> {
+        let mut attempt = 0;
+        loop {
+            let error = {
+                let _permit = self.acquire();
+                match self.agent.get(url).call() {
+                    Ok(response) => {
+                        return response
+                            .into_string()
+                            .map_err(|error| ReportError::provider(provider, error))
+                    }
+                    Err(error) => error,
+                }
+            };
+            let retry_after = match &error {
+                ureq::Error::Status(429 | 500..=599, response) => {
+                    Some(response.header("Retry-After").map(str::to_string))
+                }
+                ureq::Error::Status(..) => None,
+                ureq::Error::Transport(_) => Some(None),
+            };
+            match retry_after {
+                Some(retry_after) if attempt < self.config.retries => {
+                    let delay = self.retry_delay(attempt, retry_after.as_deref());
+                    eprintln!(
+                        "    - {} request failed, retrying in {:?}: {}",
+                        provider, delay, error
+                    );
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                _ => return Err(ReportError::provider(provider, error)),
+            }
+        }
+    }
+
+    /// Delay before retrying, from the `Retry-After` header if it's in seconds, or
+    /// from an exponential backoff
+    fn retry_delay(&self, attempt: u32, retry_after: Option<&str>) -> Duration {
+        retry_after
+            .and_then(|retry_after| retry_after.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| {
+                self.config
+                    .backoff
+                    .saturating_mul(2u32.saturating_pow(attempt))
+            })
+            .min(MAX_RETRY_DELAY)
+    }
+
+    /// Waits for a free slot, then for the interval since the previous request
+    fn acquire(&self) -> Permit<'_> {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        while *in_flight >= self.config.max_in_flight {
+            in_flight = self.released.wait(in_flight).unwrap();
+        }
+        *in_flight += 1;
+        drop(in_flight);
+
+        let wait = {
+            let mut next_request = self.next_request.lock().unwrap();
+            let now = Instant::now();
+            let start = (*next_request).max(now);
+            *next_request = start + self.config.interval;
+            start - now
+        };
+        thread::sleep(wait);
+
+        Permit { client: self }
+    }
+}
+
+struct Permit<'a> {
+    client: &'a HttpClient,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        *self.client.in_flight.lock().unwrap() -= 1;
+        self.client.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn limit_requests_in_flight() {
+        let client = HttpClient::new(HttpConfig {
+            max_in_flight: 2,
+            ..Default::default()
+        });
+        let current = AtomicUsize::new(0);
+        let max = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    let _permit = client.acquire();
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(10));
+                    current.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(max.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn space_requests() {
+        let client = HttpClient::new(HttpConfig {
+            interval: Duration::from_millis(20),
+            ..Default::default()
+        });
+        let start = Instant::now();
+        for _ in 0..3 {
+            drop(client.acquire());
+        }
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[test]
+    fn retry_delays() {
+        let client = HttpClient::new(HttpConfig {
+            backoff: Duration::from_millis(500),
+            ..Default::default()
+        });
+        assert_eq!(client.retry_delay(0, None), Duration::from_millis(500));
+        assert_eq!(client.retry_delay(2, None), Duration::from_secs(2));
+        assert_eq!(client.retry_delay(0, Some("7")), Duration::from_secs(7));
+        assert_eq!(
+            client.retry_delay(1, Some("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Duration::from_secs(1)
+        );
+        assert_eq!(client.retry_delay(20, None), MAX_RETRY_DELAY);
+    }
+}
@@ -0,0 +1,167 @@
+//! JUnit XML reports, so that CI dashboards can ingest results like test results.
+//!
+//! One file per run and platform in `junit/<run>/<platform>.xml`, with one test case per
+//! example named after its category and name.
+
+use std::{collections::HashMap, fmt::Write, fs, path::Path};
+
+use crate::{
+    analysis::log_analysis,
+    ansi,
+    error::ReportError,
+    xml::{escape, slug},
+    Example, Kind, Platform, Run,
+};
+
+/// Writes the JUnit reports of all runs in `output`.
+pub fn write_junit(
+    output: &Path,
+    runs: &[Run],
+    all_examples: &[Example],
+) -> Result<(), ReportError> {
+    let categories = all_examples
+        .iter()
+        .map(|example| (example.name.as_str(), example.category.0.as_str()))
+        .collect::<HashMap<_, _>>();
+    for run in runs {
+        let folder = output.join("junit").join(&run.id);
+        fs::create_dir_all(&folder).map_err(|error| ReportError::io(&folder, error))?;
+
+        let mut platforms = run
+            .results
+            .values()
+            .flat_map(|platforms| platforms.keys())
+            .collect::<Vec<_>>();
+        platforms.sort();
+        platforms.dedup();
+        for platform in platforms {
+            let path = folder.join(format!("{}.xml", slug(&platform.to_string())));
+            let xml = test_suite(run, platform, &categories);
+            fs::write(&path, xml).map_err(|error| ReportError::io(&path, error))?;
+        }
+    }
+    Ok(())
+}
+
+fn test_suite(run: &Run, platform: &Platform, categories: &HashMap<&str, &str>) -> String {
+    let mut examples = run
+        .results
+        .iter()
+        .filter_map(|(example, platforms)| platforms.get(platform).map(|kind| (example, kind)))
+        .collect::<Vec<_>>();
+    examples.sort_by_key(|(example, _)| *example);
+
+    let duration = |example: &str| {
+        run.durations
+            .get(example)
+            .and_then(|durations| durations.get(platform))
+            .copied()
+    };
+    let failures = examples
+        .iter()
+        .filter(|(_, kind)| **kind == Kind::Failures)
+        .count();
+    let total_duration = examples
+        .iter()
+        .filter_map(|(example, _)| duration(example))
+        .sum::<f32>();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        r#"<testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="0" time="{:.3}" timestamp="{}:00">"#,
+        escape(&format!("{} {}", platform, run.commit)),
+        examples.len(),
+        failures,
+        total_duration,
+        run.date.replace(' ', "T")
+    );
+    for (example, kind) in examples {
+        let category = categories
+            .get(example.as_str())
+            .copied()
+            .unwrap_or_default();
+        let _ = write!(
+            xml,
+            r#"  <testcase classname="{}" name="{}""#,
+            escape(category),
+            escape(example)
+        );
+        if let Some(duration) = duration(example) {
+            let _ = write!(xml, r#" time="{:.3}""#, duration);
+        }
+        if *kind != Kind::Failures {
+            xml.push_str(" />\n");
+            continue;
+        }
+        let log = run
+            .logs
+            .get(example)
+            .and_then(|logs| logs.get(&platform.to_string()))
+            .map(|log| ansi::strip(log));
+        let _ = writeln!(
+            xml,
+            ">\n    <failure message=\"{}\">{}</failure>\n  </testcase>",
+            escape(&failure_message(log.as_deref())),
+            escape(&xml_text(log.as_deref().unwrap_or_default()))
+        );
+    }
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+/// The panic or error of the log if there is one, its first line otherwise
+fn failure_message(log: Option<&str>) -> String {
+    let Some(log) = log else {
+        return "example failed".to_string();
+    };
+    log_analysis::analyze(log)
+        .message()
+        .or_else(|| {
+            log.lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string)
+        })
+        .map(|message| xml_text(&message))
+        .unwrap_or_else(|| "example failed".to_string())
+}
+
+/// Removes control characters that are not allowed in XML
+fn xml_text(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::test_util::{run, with_duration, with_log};
+
+    use super::*;
+
+    #[test]
+    fn suite_with_failure() {
+        let run = run(
+            "abc",
+            &[
+                ("lighting", Platform::Linux, Kind::Successes),
+                ("shadows", Platform::Linux, Kind::Failures),
+            ],
+        );
+        let run = with_duration(run, "lighting", Platform::Linux, 1.5);
+        let run = with_log(
+            run,
+            "shadows",
+            Platform::Linux,
+            "starting\n\u{1b}[31mthread 'main' panicked at src/main.rs:1:1: <oops>\n",
+        );
+        let categories = HashMap::from([("lighting", "3d"), ("shadows", "3d")]);
+
+        let xml = test_suite(&run, &Platform::Linux, &categories);
+        assert!(xml.contains(r#"tests="2" failures="1""#));
+        assert!(xml.contains(r#"<testcase classname="3d" name="lighting" time="1.500" />"#));
+        assert!(xml.contains(r#"<failure message="&lt;oops&gt; at src/main.rs:1:1">"#));
+        assert!(!xml.contains('\u{1b}'));
+    }
+}
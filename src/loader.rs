@@ -0,0 +1,581 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use chrono::NaiveDateTime;
+
+use crate::{
+    cache::{CacheMode, ResponseCache},
+    error::ReportError,
+    screenshot::{
+        ScreenshotData, ScreenshotProvider, ScreenshotProviders, ScreenshotSource, ScreenshotState,
+    },
+    test_report::TestReportFormat,
+    Example, ExampleCategory, Kind, Platform, Run,
+};
+
+/// Why screenshots are unavailable when they are missing from the cache in offline mode
+const NOT_CACHED: &str = "not cached (offline)";
+
+/// Reads a run folder (`<date>-<commit>`) into a [`Run`].
+///
+/// Loading a run never touches anything outside of the returned [`Run`], so a run
+/// that fails to load can be skipped without affecting the others.
+pub struct RunLoader {
+    providers: ScreenshotProviders,
+    cache: ResponseCache,
+}
+
+impl RunLoader {
+    pub fn new(providers: ScreenshotProviders, cache: ResponseCache) -> Self {
+        RunLoader { providers, cache }
+    }
+
+    /// Loads the first `limit` runs with `jobs` threads. Each run is compared to the next
+    /// folder, and results are in the same order as `folders`.
+    pub fn load_all(
+        &self,
+        folders: &[PathBuf],
+        limit: usize,
+        jobs: usize,
+    ) -> Vec<Result<Run, ReportError>> {
+        let count = folders.len().min(limit);
+        let next = AtomicUsize::new(0);
+        let runs = Mutex::new((0..count).map(|_| None).collect::<Vec<_>>());
+        thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, count.max(1)) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= count {
+                        break;
+                    }
+                    eprintln!("Processing {:?} ({})", folders[i], i);
+                    let previous_run = folders.get(i + 1).map(PathBuf::as_path);
+                    let run = self.load(&folders[i], previous_run);
+                    runs.lock().unwrap()[i] = Some(run);
+                });
+            }
+        });
+        runs.into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|run| run.expect("all runs are loaded"))
+            .collect()
+    }
+
+    /// Screenshots are compared to the ones in `previous_run` when providers don't do it.
+    pub fn load(&self, run_path: &Path, previous_run: Option<&Path>) -> Result<Run, ReportError> {
+        let folder_name = file_name(run_path)?;
+        let (date, commit) = folder_name
+            .split_once('-')
+            .ok_or_else(|| ReportError::InvalidRunFolder(folder_name.to_string()))?;
+        if commit.is_empty() {
+            return Err(ReportError::InvalidRunFolder(folder_name.to_string()));
+        }
+        let mut run = Run {
+            id: folder_name.to_string(),
+            date: NaiveDateTime::parse_from_str(date, "%Y%m%d%H%M")
+                .map_err(|error| ReportError::InvalidDate {
+                    value: date.to_string(),
+                    error,
+                })?
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            commit: commit.to_string(),
+            ..Default::default()
+        };
+
+        let mut status_files = vec![];
+        let mut test_reports = vec![];
+        let mut screenshot_sources = vec![];
+        let mut reruns = vec![];
+        for path in read_dir(run_path)? {
+            // files like `.DS_Store` are not results
+            if is_hidden(&path) {
+                continue;
+            }
+            let name = file_name(&path)?;
+            if path.is_dir() {
+                if let Some(platform) = name.strip_prefix("status-rerun-") {
+                    let platform =
+                        Platform::from_str(platform).map_err(ReportError::UnknownPlatform)?;
+                    reruns.push((path, platform));
+                } else if let Some((provider, enabled)) = self.providers.find(name) {
+                    // screenshots stored in the run folder, in `<kind>/<platform>`
+                    for platform_path in read_dir(&path)? {
+                        if platform_path.is_dir() {
+                            let platform = Platform::from_str(file_name(&platform_path)?)
+                                .map_err(ReportError::UnknownPlatform)?;
+                            screenshot_sources.push((platform_path, platform, provider, enabled));
+                        }
+                    }
+                }
+                continue;
+            }
+            let (platform, kind) = parse_file_name(name)?;
+            if let Ok(kind) = Kind::from_str(kind) {
+                status_files.push((path, platform, kind));
+            } else if let Some(format) = TestReportFormat::from_kind(kind) {
+                test_reports.push((path, platform, format));
+            } else if let Some((provider, enabled)) = self.providers.find(kind) {
+                screenshot_sources.push((path, platform, provider, enabled));
+            } else {
+                return Err(ReportError::UnknownKind(kind.to_string()));
+            }
+        }
+
+        // status files first, so that screenshots can be matched to known examples
+        for (path, platform, kind) in status_files {
+            eprintln!("[{}] - {:?} / {:?}", run.id, kind, platform);
+            self.read_status(&mut run, &path, platform, kind)?;
+        }
+        for (path, platform, format) in test_reports {
+            eprintln!("[{}] - {:?} / {:?}", run.id, format, platform);
+            self.read_test_report(&mut run, &path, platform, format)?;
+        }
+        for (path, platform, provider, enabled) in screenshot_sources {
+            if !enabled {
+                eprintln!(
+                    "[{}] - {} / {:?} (disabled)",
+                    run.id,
+                    provider.name(),
+                    platform
+                );
+                continue;
+            }
+            eprintln!("[{}] - {} / {:?}", run.id, provider.name(), platform);
+            let source = ScreenshotSource {
+                results: if path.is_dir() {
+                    String::new()
+                } else {
+                    read_to_string(&path)?
+                },
+                previous: previous_run
+                    .zip(path.strip_prefix(run_path).ok())
+                    .map(|(previous_run, source)| previous_run.join(source))
+                    .filter(|previous| previous.exists()),
+                path,
+            };
+            match self.read_screenshots(&mut run, &source, platform.clone(), provider) {
+                // a provider being down shouldn't hide the rest of the run
+                Err(error @ ReportError::Provider { .. }) => {
+                    eprintln!("[{}]   - screenshots unavailable: {}", run.id, error);
+                    run.screenshots_unavailable
+                        .insert(platform, error.to_string());
+                }
+                result => result?,
+            }
+        }
+
+        reruns.sort();
+        for (rerun, rerun_platform) in reruns {
+            eprintln!("[{}] - rerun {:?}", run.id, rerun_platform);
+            self.read_rerun(&mut run, &rerun, rerun_platform)?;
+        }
+
+        Ok(run)
+    }
+
+    fn read_status(
+        &self,
+        run: &mut Run,
+        path: &Path,
+        platform: Platform,
+        kind: Kind,
+    ) -> Result<(), ReportError> {
+        for line in read_to_string(path)?.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (category, name, duration) = parse_status_line(path, line)?;
+            let example = Example::new(name, category);
+            run.results
+                .entry(example.name.clone())
+                .or_default()
+                .insert(platform.clone(), kind.clone());
+            if let Some(duration) = duration {
+                run.durations
+                    .entry(example.name.clone())
+                    .or_default()
+                    .insert(platform.clone(), duration);
+            }
+            run.examples.insert(example);
+        }
+        Ok(())
+    }
+
+    fn read_test_report(
+        &self,
+        run: &mut Run,
+        path: &Path,
+        platform: Platform,
+        format: TestReportFormat,
+    ) -> Result<(), ReportError> {
+        for result in format.parse(path, &read_to_string(path)?)? {
+            let example = Example::new(result.name, result.category);
+            run.results
+                .entry(example.name.clone())
+                .or_default()
+                .insert(platform.clone(), result.kind);
+            if let Some(duration) = result.duration {
+                run.durations
+                    .entry(example.name.clone())
+                    .or_default()
+                    .insert(platform.clone(), duration);
+            }
+            if let Some(log) = result.log {
+                run.logs
+                    .entry(example.name.clone())
+                    .or_default()
+                    .insert(platform.to_string(), log);
+            }
+            run.examples.insert(example);
+        }
+        Ok(())
+    }
+
+    fn read_screenshots(
+        &self,
+        run: &mut Run,
+        source: &ScreenshotSource,
+        platform: Platform,
+        provider: &dyn ScreenshotProvider,
+    ) -> Result<(), ReportError> {
+        let Some(response) = self.fetch(source, provider)? else {
+            return Ok(());
+        };
+        let screenshots = provider.convert(source, &response)?;
+        for ScreenshotData {
+            mut example,
+            screenshot,
+            mut changed,
+            mut tag,
+            diff_ratio,
+            snapshot_url,
+        } in screenshots.into_iter()
+        {
+            let (category, name) = if platform == Platform::Mobile {
+                if let Some(tag) = tag.as_ref() {
+                    run.mobile_platforms.insert(tag.clone());
+                } else {
+                    let parts = example.split('-').collect::<Vec<_>>();
+                    let [os, device, version, ..] = parts[..] else {
+                        return Err(ReportError::InvalidScreenshotName(example));
+                    };
+                    let mobile_platform = format!("{} {} / {}", os, version, device);
+                    run.mobile_platforms.insert(mobile_platform.clone());
+                    tag = Some(mobile_platform);
+                    example = "Bevy Mobile Example".to_string();
+                }
+                (ExampleCategory("Mobile".to_string()), example)
+            } else {
+                let mut split = example.split('.').next().unwrap_or_default().split('/');
+                match (split.next(), split.next()) {
+                    (Some(category), Some(name)) => {
+                        (ExampleCategory(category.to_string()), name.to_string())
+                    }
+                    // wasm screenshots are only named after the example
+                    (Some(name), None) if matches!(platform, Platform::Wasm { .. }) => {
+                        let category = run
+                            .examples
+                            .iter()
+                            .find(|known| known.name == name)
+                            .map(|known| known.category.clone())
+                            .unwrap_or_else(|| ExampleCategory("Wasm".to_string()));
+                        (category, name.to_string())
+                    }
+                    _ => return Err(ReportError::InvalidScreenshotName(example)),
+                }
+            };
+            let example = Example::new(name, category);
+            run.examples.insert(example.clone());
+            if diff_ratio == 0.0 && changed == ScreenshotState::Changed {
+                eprintln!(
+                    "[{}]   - setting {} / {} ({:?}) as unchanged",
+                    run.id, example.category.0, example.name, tag
+                );
+                changed = ScreenshotState::Similar;
+            }
+            let platform = tag
+                .clone()
+                .map(|tag| Platform::Tag(tag.clone()))
+                .unwrap_or_else(|| platform.clone());
+            // If there is a screenshot but no results, mark as success
+            run.results
+                .entry(example.name.clone())
+                .or_default()
+                .entry(platform.clone())
+                .or_insert_with(|| Kind::Successes);
+            run.screenshots
+                .entry(example.name)
+                .or_default()
+                .insert(platform.clone(), (screenshot, changed, snapshot_url));
+        }
+        Ok(())
+    }
+
+    /// Fetches screenshots from a provider, going through the cache when possible. In offline
+    /// mode, responses missing from the cache are a provider error so that the platform is
+    /// marked as having its screenshots unavailable, as are sources that can't be read.
+    fn fetch(
+        &self,
+        source: &ScreenshotSource,
+        provider: &dyn ScreenshotProvider,
+    ) -> Result<Option<String>, ReportError> {
+        let offline = self.cache.mode() == CacheMode::Offline;
+        let Some(key) = provider.cache_key(source)? else {
+            if offline && provider.is_remote() {
+                return Err(ReportError::provider(provider.name(), NOT_CACHED));
+            }
+            return provider.fetch(source);
+        };
+        if let Some(response) = self.cache.get(&key) {
+            return Ok(Some(response));
+        }
+        if offline {
+            return Err(ReportError::provider(provider.name(), NOT_CACHED));
+        }
+        let response = provider.fetch(source)?;
+        if let Some(response) = response.as_ref().filter(|r| provider.is_final(r)) {
+            if let Err(error) = self.cache.store(&key, response) {
+                eprintln!("can't cache response {}: {}", key, error);
+            }
+        }
+        Ok(response)
+    }
+
+    fn read_rerun(
+        &self,
+        run: &mut Run,
+        rerun: &Path,
+        rerun_platform: Platform,
+    ) -> Result<(), ReportError> {
+        for path in read_dir(rerun)? {
+            let kind = file_name(&path)?;
+            if kind == "successes" {
+                eprintln!("[{}]   - {} / {:?}", run.id, kind, rerun_platform);
+                for line in read_to_string(&path)?.lines() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let (_, name, _) = parse_status_line(&path, line)?;
+                    run.results
+                        .entry(name)
+                        .or_default()
+                        .insert(rerun_platform.clone(), Kind::NoScreenshots);
+                }
+            }
+            if let Some(example_name) = kind.strip_suffix(".log") {
+                eprintln!(
+                    "[{}]   - log / {:?} ({})",
+                    run.id, rerun_platform, example_name
+                );
+                // escape sequences are kept, for the site to show colors
+                let log = read_to_string(&path)?;
+                run.logs
+                    .entry(example_name.to_string())
+                    .or_default()
+                    .insert(rerun_platform.to_string(), log);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Result files are named `<platform>-<kind>`, the platform can contain dashes.
+fn parse_file_name(file_name: &str) -> Result<(Platform, &str), ReportError> {
+    let (platform, kind) = file_name
+        .rsplit_once('-')
+        .ok_or_else(|| ReportError::UnknownKind(file_name.to_string()))?;
+    let platform = Platform::from_str(platform).map_err(ReportError::UnknownPlatform)?;
+    Ok((platform, kind))
+}
+
+/// Parses a `category/name - duration` line from a status file.
+fn parse_status_line(
+    path: &Path,
+    line: &str,
+) -> Result<(ExampleCategory, String, Option<f32>), ReportError> {
+    let invalid = || ReportError::InvalidResultLine {
+        path: path.to_path_buf(),
+        line: line.to_string(),
+    };
+    let mut parts = line.split(" - ");
+    let mut details = parts.next().ok_or_else(invalid)?.split('/');
+    let (Some(category), Some(name)) = (details.next(), details.next()) else {
+        return Err(invalid());
+    };
+    if category.is_empty() || name.is_empty() {
+        return Err(invalid());
+    }
+    // durations are optional, a malformed one doesn't make the result invalid
+    let duration = parts
+        .next()
+        .and_then(|duration| match duration.trim().parse::<f32>() {
+            Ok(duration) => Some(duration),
+            Err(_) => {
+                eprintln!("ignoring invalid duration in {:?}: {:?}", path, line);
+                None
+            }
+        });
+    Ok((
+        ExampleCategory(category.to_string()),
+        name.to_string(),
+        duration,
+    ))
+}
+
+pub fn read_dir(path: &Path) -> Result<Vec<PathBuf>, ReportError> {
+    fs::read_dir(path)
+        .map_err(|error| ReportError::io(path, error))?
+        .map(|entry| {
+            entry
+                .map(|entry| entry.path())
+                .map_err(|error| ReportError::io(path, error))
+        })
+        .collect()
+}
+
+/// Dot-files and folders, left by the system or tools
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+fn read_to_string(path: &Path) -> Result<String, ReportError> {
+    fs::read_to_string(path).map_err(|error| ReportError::io(path, error))
+}
+
+fn file_name(path: &Path) -> Result<&str, ReportError> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| ReportError::InvalidRunFolder(path.display().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    /// A run folder named `<date>-<test>` with the given files, in a temporary folder to
+    /// remove with its parent
+    fn run_folder(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let folder = std::env::temp_dir()
+            .join(format!("example-report-{}-{}", test, std::process::id()))
+            .join(format!("202501011200-{}", test));
+        fs::create_dir_all(&folder).unwrap();
+        for (name, content) in files {
+            fs::write(folder.join(name), content).unwrap();
+        }
+        folder
+    }
+
+    fn loader(mode: CacheMode) -> RunLoader {
+        RunLoader::new(
+            ScreenshotProviders::new(Path::new("site"), Default::default()),
+            ResponseCache::new(Path::new("cache"), mode),
+        )
+    }
+
+    #[test]
+    fn parse_status_lines() {
+        let path = Path::new("Linux-successes");
+        let (category, name, duration) = parse_status_line(path, "3d/lighting - 12.34").unwrap();
+        assert_eq!(category.0, "3d");
+        assert_eq!(name, "lighting");
+        assert_eq!(duration, Some(12.34));
+        let (_, _, duration) = parse_status_line(path, "3d/lighting").unwrap();
+        assert_eq!(duration, None);
+        assert!(parse_status_line(path, "README").is_err());
+    }
+
+    #[test]
+    fn ignore_malformed_durations() {
+        let path = Path::new("Linux-successes");
+        let (category, name, duration) = parse_status_line(path, "3d/lighting - soon").unwrap();
+        assert_eq!(category.0, "3d");
+        assert_eq!(name, "lighting");
+        assert_eq!(duration, None);
+    }
+
+    #[test]
+    fn parse_file_names() {
+        assert_eq!(
+            parse_file_name("Linux-successes").unwrap(),
+            (Platform::Linux, "successes")
+        );
+        assert_eq!(
+            parse_file_name("wasm-firefox-webgl2-pixeleagle").unwrap(),
+            (
+                Platform::Wasm {
+                    browser: "firefox".to_string(),
+                    api: "webgl2".to_string()
+                },
+                "pixeleagle"
+            )
+        );
+        assert_eq!(
+            parse_file_name("Linux-ARM-no_screenshots").unwrap(),
+            (Platform::Other("Linux-ARM".to_string()), "no_screenshots")
+        );
+        assert!(parse_file_name("wasm-firefox-successes").is_err());
+        assert!(parse_file_name("README").is_err());
+    }
+
+    #[test]
+    fn invalid_run_folder() {
+        let loader = loader(CacheMode::Offline);
+        assert!(matches!(
+            loader.load(Path::new("README"), None),
+            Err(ReportError::InvalidRunFolder(_))
+        ));
+        assert!(matches!(
+            loader.load(Path::new("notadate-abcdef"), None),
+            Err(ReportError::InvalidDate { .. })
+        ));
+    }
+
+    #[test]
+    fn skip_hidden_files() {
+        let folder = run_folder(
+            "hidden",
+            &[
+                ("Linux-successes", "3d/lighting\n"),
+                (".DS_Store", "\0\0\0\u{1}Bud1"),
+            ],
+        );
+        let run = loader(CacheMode::Offline).load(&folder, None).unwrap();
+        assert_eq!(run.results["lighting"][&Platform::Linux], Kind::Successes);
+        fs::remove_dir_all(folder.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn malformed_provider_file() {
+        let folder = run_folder(
+            "malformed",
+            &[
+                ("Linux-successes", "3d/lighting\n"),
+                ("Linux-pixeleagle", "<html>502 Bad Gateway</html>"),
+            ],
+        );
+        for mode in [CacheMode::Offline, CacheMode::Normal] {
+            let run = loader(mode).load(&folder, None).unwrap();
+            let reason = &run.screenshots_unavailable[&Platform::Linux];
+            assert!(
+                reason.starts_with("pixeleagle: expected value"),
+                "{}",
+                reason
+            );
+            assert_eq!(run.results["lighting"][&Platform::Linux], Kind::Successes);
+        }
+        fs::remove_dir_all(folder.parent().unwrap()).unwrap();
+    }
+}
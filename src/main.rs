@@ -1,23 +1,63 @@
-use chrono::NaiveDateTime;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use serde::Serialize;
 use std::{
-    collections::{HashMap, HashSet},
-    fs,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt, fs,
     hash::Hash,
+    path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
+    time::Duration,
 };
 
-use crate::screenshot::{percy, pixeleagle, ScreenshotData, ScreenshotState};
+use crate::{
+    cache::{CacheMode, ResponseCache},
+    error::{IngestionProblem, ReportError},
+    http::{HttpClient, HttpConfig},
+    loader::RunLoader,
+    screenshot::{ScreenshotProviders, ScreenshotState},
+};
 
+mod analysis;
+mod ansi;
+mod badge;
+mod cache;
+mod diff;
+mod error;
+mod export;
+mod http;
+mod junit;
+mod loader;
+mod markdown;
 mod screenshot;
 mod template;
+mod test_report;
+mod xml;
 
 #[derive(Debug, Clone, Serialize)]
 struct Example {
     name: String,
     category: ExampleCategory,
-    flaky: bool,
+    flakiness: analysis::flakiness::Flakiness,
+    classification: analysis::classification::Classification,
+    /// Latest changes of result and screenshot, for each platform
+    state_changes: Vec<analysis::first_bad_commit::StateChange>,
+    /// Durations in seconds for each platform, in the same order as the runs
+    durations: BTreeMap<String, Vec<Option<f32>>>,
+}
+
+impl Example {
+    /// An example without analysis, filled in once all runs are loaded
+    fn new(name: String, category: ExampleCategory) -> Self {
+        Example {
+            name,
+            category,
+            flakiness: Default::default(),
+            classification: Default::default(),
+            state_changes: Default::default(),
+            durations: Default::default(),
+        }
+    }
 }
 
 impl PartialEq for Example {
@@ -35,11 +75,32 @@ impl Hash for Example {
 
 #[derive(Debug, Serialize, Default)]
 struct Run {
+    /// Name of the run folder, `<date>-<commit>`
+    id: String,
     date: String,
     commit: String,
     results: HashMap<String, HashMap<Platform, Kind>>,
     screenshots: HashMap<String, HashMap<Platform, (ImageUrl, ScreenshotState, SnapshotViewerUrl)>>,
     logs: HashMap<String, HashMap<String, String>>,
+    /// How long each example ran, in seconds
+    durations: HashMap<String, HashMap<Platform, f32>>,
+    /// Platforms whose screenshots couldn't be fetched, with the reason
+    screenshots_unavailable: HashMap<Platform, String>,
+    /// Examples seen in this run, merged into the report once the run is loaded
+    #[serde(skip)]
+    examples: HashSet<Example>,
+    #[serde(skip)]
+    mobile_platforms: HashSet<String>,
+}
+
+impl Run {
+    /// First 7 characters of the commit, as displayed by GitHub
+    fn short_commit(&self) -> &str {
+        match self.commit.char_indices().nth(7) {
+            Some((end, _)) => &self.commit[..end],
+            None => &self.commit,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Clone, PartialEq, Eq, Hash)]
@@ -51,23 +112,39 @@ struct ImageUrl(String);
 #[derive(Debug, Serialize, Clone, PartialEq, Eq, Hash)]
 struct SnapshotViewerUrl(String);
 
-#[derive(Debug, Serialize, PartialEq, Eq, Hash, Clone)]
+/// Platforms are ordered as they are displayed in the report.
+#[derive(Debug, Serialize, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
 enum Platform {
     Linux,
     Macos,
     Windows,
+    /// Any other runner, named after its result files
+    Other(String),
+    Wasm {
+        browser: String,
+        api: String,
+    },
     Mobile,
     Tag(String),
 }
 
-impl ToString for Platform {
-    fn to_string(&self) -> String {
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Platform::Linux => String::from("Linux"),
-            Platform::Macos => String::from("macOS"),
-            Platform::Windows => String::from("Windows"),
-            Platform::Mobile => String::from("Mobile"),
-            Platform::Tag(tag) => tag.clone(),
+            Platform::Linux => write!(f, "Linux"),
+            Platform::Macos => write!(f, "macOS"),
+            Platform::Windows => write!(f, "Windows"),
+            Platform::Mobile => write!(f, "Mobile"),
+            Platform::Wasm { browser, api } => {
+                let api = match api.as_str() {
+                    "webgl2" => "WebGL2",
+                    "webgpu" => "WebGPU",
+                    api => api,
+                };
+                write!(f, "{} / {}", api, browser)
+            }
+            Platform::Tag(tag) => write!(f, "{}", tag),
+            Platform::Other(name) => write!(f, "{}", name),
         }
     }
 }
@@ -78,10 +155,24 @@ impl FromStr for Platform {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "Linux" => Ok(Platform::Linux),
-            "macOS" => Ok(Platform::Macos),
+            // rerun folders are named after the `Debug` representation
+            "macOS" | "Macos" => Ok(Platform::Macos),
             "Windows" => Ok(Platform::Windows),
             "mobile" => Ok(Platform::Mobile),
-            _ => Err(s.to_string()),
+            _ if s.starts_with("wasm-") => {
+                let mut parts = s.splitn(3, '-').skip(1);
+                match (parts.next(), parts.next()) {
+                    (Some(browser), Some(api)) if !browser.is_empty() && !api.is_empty() => {
+                        Ok(Platform::Wasm {
+                            browser: browser.to_string(),
+                            api: api.to_string(),
+                        })
+                    }
+                    _ => Err(s.to_string()),
+                }
+            }
+            "" => Err(s.to_string()),
+            _ => Ok(Platform::Other(s.to_string())),
         }
     }
 }
@@ -91,8 +182,6 @@ enum Kind {
     Successes,
     Failures,
     NoScreenshots,
-    Percy,
-    PixelEagle,
 }
 
 impl FromStr for Kind {
@@ -103,229 +192,468 @@ impl FromStr for Kind {
             "successes" => Ok(Kind::Successes),
             "failures" => Ok(Kind::Failures),
             "no_screenshots" => Ok(Kind::NoScreenshots),
-            "percy" => Ok(Kind::Percy),
-            "pixeleagle" => Ok(Kind::PixelEagle),
             _ => Err(s.to_string()),
         }
     }
 }
 
-/// Generates the example report site
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
-struct Args {
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    Build(BuildArgs),
+    Summary(SummaryArgs),
+    Diff(DiffArgs),
+    Query(QueryArgs),
+    Validate(ValidateArgs),
+}
+
+/// Generates the example report site
+#[derive(clap::Args, Debug)]
+struct BuildArgs {
     /// Path to the directory containing processed results.
     path: std::path::PathBuf,
 
     /// Limit the number of results processed. Defaults to 30.
     #[arg(long, default_value_t = 30)]
     limit: usize,
+
+    /// Abort on the first run that can't be read instead of skipping it.
+    #[arg(long)]
+    strict: bool,
+
+    /// Report an example as slower when it takes this many times its usual duration.
+    #[arg(long, default_value_t = 2.0, value_parser = parse_slowdown_factor)]
+    slowdown_factor: f32,
+
+    /// Number of previous runs used to compute the usual duration of an example.
+    #[arg(
+        long,
+        default_value_t = 5,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    slowdown_window: usize,
+
+    /// URL where the site is published, used for absolute links in the feed.
+    #[arg(
+        long,
+        default_value = "https://thebevyflock.github.io/bevy-example-runner/"
+    )]
+    site_url: String,
+
+    /// Number of runs loaded in parallel.
+    #[arg(long, short, default_value_t = 4)]
+    jobs: usize,
+
+    /// Directory where the site is written.
+    #[arg(long, short, default_value = "./site")]
+    output: PathBuf,
+
+    /// Directory with templates replacing the ones embedded in the binary.
+    #[arg(long)]
+    templates: Option<PathBuf>,
+
+    #[command(flatten)]
+    loader: LoaderArgs,
+}
+
+/// Writes a Markdown summary of a run compared to a baseline run, for PR comments
+#[derive(clap::Args, Debug)]
+struct SummaryArgs {
+    /// Path to the run folder.
+    run: PathBuf,
+
+    /// Path to the run folder to compare with.
+    baseline: PathBuf,
+
+    /// File where the summary is written.
+    #[arg(long, short, default_value = "summary.md")]
+    output: PathBuf,
+
+    /// URL where the site is published, used for links to local screenshots.
+    #[arg(
+        long,
+        default_value = "https://thebevyflock.github.io/bevy-example-runner/"
+    )]
+    site_url: String,
+
+    /// Directory of the site, where local screenshots are copied.
+    #[arg(long, default_value = "./site")]
+    site_dir: PathBuf,
+
+    #[command(flatten)]
+    loader: LoaderArgs,
+}
+
+/// Lists the results and screenshots that differ between two runs
+#[derive(clap::Args, Debug)]
+struct DiffArgs {
+    /// Path to the first run folder.
+    from: PathBuf,
+
+    /// Path to the second run folder, whose screenshots are compared to the first one.
+    to: PathBuf,
+
+    #[command(flatten)]
+    loader: LoaderArgs,
+}
+
+/// Shows the history of an example across runs
+#[derive(clap::Args, Debug)]
+struct QueryArgs {
+    /// Path to the directory containing processed results.
+    path: PathBuf,
+
+    /// Name of the example, without its category.
+    #[arg(long)]
+    example: String,
+
+    /// Limit the number of results processed.
+    #[arg(long, default_value_t = 30)]
+    limit: usize,
+
+    /// Number of runs loaded in parallel.
+    #[arg(long, short, default_value_t = 4)]
+    jobs: usize,
+
+    #[command(flatten)]
+    loader: LoaderArgs,
+}
+
+/// Checks that all runs in the results folder can be read, without fetching screenshots
+/// or generating anything
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    /// Path to the directory containing processed results.
+    path: PathBuf,
+
+    /// Number of runs loaded in parallel.
+    #[arg(long, short, default_value_t = 4)]
+    jobs: usize,
+}
+
+/// How runs are read
+#[derive(clap::Args, Debug)]
+struct LoaderArgs {
+    /// Read screenshots from a provider that is disabled by default (e.g. `percy`).
+    #[arg(long = "enable-provider", value_name = "PROVIDER")]
+    enable_providers: Vec<String>,
+
+    /// Don't read screenshots from a provider (e.g. `pixeleagle`).
+    #[arg(long = "disable-provider", value_name = "PROVIDER")]
+    disable_providers: Vec<String>,
+
+    /// Directory where screenshot provider responses are cached.
+    #[arg(long, default_value = "./cache")]
+    cache_dir: PathBuf,
+
+    /// Build the site only from cached screenshot provider responses.
+    #[arg(long, conflicts_with = "refresh")]
+    offline: bool,
+
+    /// Ignore cached screenshot provider responses and fetch them again.
+    #[arg(long)]
+    refresh: bool,
+
+    /// Maximum number of requests to screenshot providers in flight at the same time.
+    #[arg(long, default_value_t = 4)]
+    http_requests: usize,
+
+    /// Minimum delay between two requests to screenshot providers, in milliseconds.
+    #[arg(long, default_value_t = 0)]
+    http_interval: u64,
+
+    /// Timeout for requests to screenshot providers, in seconds.
+    #[arg(long, default_value_t = 30)]
+    http_timeout: u64,
+
+    /// Number of retries for requests to screenshot providers that timed out or
+    /// failed with a 429 or 5xx status.
+    #[arg(long, default_value_t = 3)]
+    http_retries: u32,
+
+    /// Delay before the first retry, doubled for each following one, in milliseconds.
+    #[arg(long, default_value_t = 1000)]
+    http_backoff: u64,
+}
+
+impl LoaderArgs {
+    /// `output` is the site folder, where local screenshots are copied
+    fn loader(&self, output: &Path) -> Result<RunLoader, ReportError> {
+        let http = HttpClient::new(HttpConfig {
+            max_in_flight: self.http_requests,
+            interval: Duration::from_millis(self.http_interval),
+            timeout: Duration::from_secs(self.http_timeout),
+            retries: self.http_retries,
+            backoff: Duration::from_millis(self.http_backoff),
+        });
+        let mut providers = ScreenshotProviders::new(output, Arc::new(http));
+        for provider in &self.enable_providers {
+            providers.set_enabled(provider, true)?;
+        }
+        for provider in &self.disable_providers {
+            providers.set_enabled(provider, false)?;
+        }
+        let cache_mode = if self.offline {
+            CacheMode::Offline
+        } else if self.refresh {
+            CacheMode::Refresh
+        } else {
+            CacheMode::Normal
+        };
+        Ok(RunLoader::new(
+            providers,
+            ResponseCache::new(&self.cache_dir, cache_mode),
+        ))
+    }
 }
 
 fn main() {
-    let args = Args::parse();
+    let result = match Cli::parse().command {
+        Command::Build(args) => build(args),
+        Command::Summary(args) => summary(args),
+        Command::Diff(args) => diff(args),
+        Command::Query(args) => query(args),
+        Command::Validate(args) => validate(args),
+    };
 
-    let paths = fs::read_dir(args.path).unwrap();
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
 
-    let _ = fs::create_dir("./site");
+fn build(args: BuildArgs) -> Result<(), ReportError> {
+    fs::create_dir_all(&args.output).map_err(|error| ReportError::io(&args.output, error))?;
+    // fail on invalid templates before loading runs
+    let site = template::Site::new(&args.output, args.templates.as_deref())?;
 
     let mut all_examples = HashSet::new();
     let mut runs = vec![];
     let mut all_mobile_platforms = HashSet::new();
+    let mut problems = vec![];
+
+    let folders = run_folders(&args.path)?;
+    let loader = args.loader.loader(&args.output)?;
+    let loaded = loader.load_all(&folders, args.limit, args.jobs);
+    for (run_path, run) in folders.iter().zip(loaded) {
+        let mut run = match run {
+            Ok(run) => run,
+            Err(error) if !args.strict => {
+                println!("Skipping {:?}: {}", run_path, error);
+                problems.push(IngestionProblem {
+                    path: run_path.display().to_string(),
+                    error: error.to_string(),
+                });
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        all_examples.extend(run.examples.drain());
+        all_mobile_platforms.extend(run.mobile_platforms.drain());
+        runs.push(run);
+    }
+
+    let mut all_examples_cleaned = Vec::new();
+    for mut example in all_examples.drain() {
+        example.flakiness = analysis::flakiness::flakiness(&runs, &example.name);
+        example.classification = analysis::classification::classify(&runs, &example.name);
+        example.state_changes =
+            analysis::first_bad_commit::find_state_changes(&runs, &example.name);
+        example.durations = duration_history(&runs, &example.name);
+        all_examples_cleaned.push(example);
+    }
 
-    let mut folders = paths
-        .filter_map(|dir| dir.map(|d| d.path()).ok())
+    all_examples_cleaned.sort_by_key(|a| format!("{}/{}", a.category.0, a.name));
+
+    // mobile examples are displayed separately, one row per device
+    let all_platforms = runs
+        .iter()
+        .flat_map(|run| run.results.values())
+        .flat_map(|platforms| platforms.keys())
+        .filter(|platform| !matches!(platform, Platform::Mobile | Platform::Tag(_)))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|platform| platform.to_string())
         .collect::<Vec<_>>();
-    folders.sort();
-    folders.reverse();
 
-    for (i, run_path) in folders.iter().take(args.limit).enumerate() {
-        let file_name = run_path.file_name().unwrap().to_str().unwrap();
-        if file_name.starts_with(".") {
-            continue;
-        }
-        println!("Processing {:?} ({})", run_path, i);
-        let mut split = file_name.split('-');
-        let mut run = Run {
-            date: NaiveDateTime::parse_from_str(split.next().unwrap(), "%Y%m%d%H%M")
-                .unwrap()
-                .format("%Y-%m-%d %H:%M")
-                .to_string(),
-            commit: split.next().unwrap().to_string(),
-            ..Default::default()
-        };
+    let slowdowns =
+        analysis::slowdown::find_slowdowns(&runs, args.slowdown_window, args.slowdown_factor);
+    let summaries = analysis::run_summary::summarize_all(&runs);
 
-        for file in fs::read_dir(run_path).unwrap() {
-            let file = file.as_ref().unwrap();
-            if file.file_type().unwrap().is_dir() {
+    export::write_json(
+        &args.output,
+        &runs,
+        &all_examples_cleaned,
+        &all_mobile_platforms,
+        &all_platforms,
+    )?;
+
+    junit::write_junit(&args.output, &runs, &all_examples_cleaned)?;
+    badge::write_badges(
+        &args.output,
+        runs.first().zip(summaries.first()),
+        &all_examples_cleaned,
+    )?;
+    site.build_feed(&summaries, &args.site_url)?;
+    site.build_site(template::Report {
+        runs,
+        all_examples: all_examples_cleaned,
+        all_mobile_platforms,
+        all_platforms,
+        problems,
+        slowdowns,
+        summaries,
+    })
+}
+
+fn summary(args: SummaryArgs) -> Result<(), ReportError> {
+    let loader = args.loader.loader(&args.site_dir)?;
+    let baseline = loader.load(&args.baseline, None)?;
+    let run = loader.load(&args.run, Some(&args.baseline))?;
+
+    let markdown = markdown::summary(&run, &baseline, &args.site_url);
+    fs::write(&args.output, markdown).map_err(|error| ReportError::io(&args.output, error))?;
+    println!("Summary written to {:?}", args.output);
+    Ok(())
+}
+
+fn diff(args: DiffArgs) -> Result<(), ReportError> {
+    // screenshots are only needed to compare them, not to display them
+    let loader = args.loader.loader(&scratch_dir())?;
+    let from = loader.load(&args.from, None)?;
+    let to = loader.load(&args.to, Some(&args.from))?;
+
+    let lines = diff::diff(&from, &to);
+    if lines.is_empty() {
+        println!("No differences");
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn query(args: QueryArgs) -> Result<(), ReportError> {
+    let folders = run_folders(&args.path)?;
+    let loader = args.loader.loader(&scratch_dir())?;
+    let loaded = loader.load_all(&folders, args.limit, args.jobs);
+
+    let mut found = false;
+    for (run_path, run) in folders.iter().zip(loaded) {
+        let run = match run {
+            Ok(run) => run,
+            Err(error) => {
+                eprintln!("Skipping {:?}: {}", run_path, error);
                 continue;
             }
-            let path = file.path();
-            let mut name = path.file_name().unwrap().to_str().unwrap().split('-');
-            let platform = Platform::from_str(name.next().unwrap()).unwrap();
-            let kind = Kind::from_str(name.next().unwrap()).unwrap();
-
-            if [Kind::Successes, Kind::Failures, Kind::NoScreenshots].contains(&kind) {
-                println!("  - {:?} / {:?}", kind, platform);
-                fs::read_to_string(&path).unwrap().lines().for_each(|line| {
-                    let mut line = line.split(" - ");
-                    let mut details = line.next().unwrap().split('/');
-                    let example = Example {
-                        category: ExampleCategory(details.next().unwrap().to_string()),
-                        name: details.next().unwrap().to_string(),
-                        flaky: kind != Kind::Successes,
-                    };
-                    let previous = all_examples.take(&example);
-                    all_examples.insert(Example {
-                        flaky: previous.map(|ex: Example| ex.flaky).unwrap_or(false)
-                            || example.flaky,
-                        ..example.clone()
-                    });
-                    run.results
-                        .entry(example.name)
-                        .or_insert_with(HashMap::new)
-                        .insert(platform.clone(), kind.clone());
-                });
-            }
-            if [Kind::Percy, Kind::PixelEagle].contains(&kind) {
-                println!("  - {:?} / {:?}", kind, platform);
-                let content = fs::read_to_string(&path).unwrap();
-                let screenshots = match kind {
-                    Kind::Percy => continue, //percy::read_results(content),
-                    Kind::PixelEagle => pixeleagle::read_results(content),
-                    _ => unreachable!(),
-                };
-                for ScreenshotData {
-                    mut example,
-                    screenshot,
-                    mut changed,
-                    mut tag,
-                    diff_ratio,
-                    snapshot_url,
-                } in screenshots.into_iter()
-                {
-                    let (category, name) = if platform == Platform::Mobile {
-                        if let Some(tag) = tag.as_ref() {
-                            all_mobile_platforms.insert(tag.clone());
-                        } else {
-                            let parts = example.split('-').collect::<Vec<_>>();
-                            tag = Some(format!("{} {} / {}", parts[0], parts[2], parts[1]));
-                            all_mobile_platforms.insert(tag.clone().unwrap());
-                            example = "Bevy Mobile Example".to_string();
-                        }
-                        (ExampleCategory("Mobile".to_string()), example)
-                    } else {
-                        let mut split = example.split('.').next().unwrap().split('/');
-                        (
-                            ExampleCategory(split.next().unwrap().to_string()),
-                            split.next().unwrap().to_string(),
-                        )
-                    };
-                    let example = Example {
-                        category,
-                        name,
-                        flaky: false,
-                    };
-                    if changed == ScreenshotState::Changed {
-                        let previous = all_examples.take(&example).unwrap_or(example.clone());
-                        all_examples.insert(Example {
-                            flaky: true,
-                            ..previous
-                        });
-                    } else {
-                        let previous = all_examples.take(&example).unwrap_or(example.clone());
-                        all_examples.insert(previous);
-                    }
-                    if diff_ratio == 0.0 && changed == ScreenshotState::Changed {
-                        println!(
-                            "    - setting {} / {} ({:?}) as unchanged",
-                            example.category.0, example.name, tag
-                        );
-                        changed = ScreenshotState::Similar;
-                    }
-                    let platform = tag
-                        .clone()
-                        .map(|tag| Platform::Tag(tag.clone()))
-                        .unwrap_or_else(|| platform.clone());
-                    // If there is a screenshot but no results, mark as success
-                    run.results
-                        .entry(example.name.clone())
-                        .or_insert_with(HashMap::new)
-                        .entry(platform.clone())
-                        .or_insert_with(|| Kind::Successes);
-                    run.screenshots
-                        .entry(example.name)
-                        .or_insert_with(HashMap::new)
-                        .insert(platform.clone(), (screenshot, changed, snapshot_url));
-                }
+        };
+        let Some(results) = run.results.get(&args.example) else {
+            continue;
+        };
+        found = true;
+        let mut platforms = results.keys().collect::<Vec<_>>();
+        platforms.sort();
+        println!("{} {}", run.date, run.short_commit());
+        for platform in platforms {
+            let description = diff::describe(&run, &args.example, platform).unwrap_or_default();
+            match run
+                .durations
+                .get(&args.example)
+                .and_then(|durations| durations.get(platform))
+            {
+                Some(duration) => println!("  {}: {} ({:.1}s)", platform, description, duration),
+                None => println!("  {}: {}", platform, description),
             }
         }
-        for rerun_platform in [Platform::Linux, Platform::Windows, Platform::Macos] {
-            let rerun = run_path.join(format!("status-rerun-{:?}", rerun_platform));
-            if rerun.exists() {
-                println!("  - rerun {:?}", rerun_platform);
-                for file in fs::read_dir(rerun.as_path()).unwrap() {
-                    let path = file.as_ref().unwrap().path();
-                    let kind = path.file_name().unwrap().to_str().unwrap();
-                    if kind == "successes" {
-                        println!("    - {} / {:?}", kind, rerun_platform);
-                        fs::read_to_string(file.as_ref().unwrap().path())
-                            .unwrap()
-                            .lines()
-                            .for_each(|line| {
-                                let mut line = line.split(" - ");
-                                let mut details = line.next().unwrap().split('/');
-                                let example = Example {
-                                    category: ExampleCategory(details.next().unwrap().to_string()),
-                                    name: details.next().unwrap().to_string(),
-                                    flaky: false,
-                                };
-                                run.results
-                                    .entry(example.name)
-                                    .or_insert_with(HashMap::new)
-                                    .insert(rerun_platform.clone(), Kind::NoScreenshots);
-                            });
-                    }
-                    if kind.ends_with(".log") {
-                        let example_name = kind.strip_suffix(".log").unwrap();
-                        println!("    - log / {:?} ({})", rerun_platform, example_name);
-                        let mut log = fs::read_to_string(file.as_ref().unwrap().path()).unwrap();
-                        log = log.replace("[0m", "");
-                        log = log.replace("[1m", "");
-                        log = log.replace("[2m", "");
-                        log = log.replace("[31m", "");
-                        log = log.replace("[32m", "");
-                        log = log.replace("[33m", "");
-                        run.logs
-                            .entry(example_name.to_string())
-                            .or_insert_with(HashMap::new)
-                            .insert(rerun_platform.to_string(), log);
-                    }
-                }
+    }
+    if !found {
+        println!("No results for {:?}", args.example);
+    }
+    Ok(())
+}
+
+fn validate(args: ValidateArgs) -> Result<(), ReportError> {
+    let folders = run_folders(&args.path)?;
+    let mut providers = ScreenshotProviders::new(&scratch_dir(), Default::default());
+    providers.disable_all();
+    let loader = RunLoader::new(
+        providers,
+        ResponseCache::new(&scratch_dir(), CacheMode::Offline),
+    );
+
+    let mut invalid = 0;
+    for (run_path, run) in folders
+        .iter()
+        .zip(loader.load_all(&folders, usize::MAX, args.jobs))
+    {
+        match run {
+            Ok(_) => println!("ok      {}", run_path.display()),
+            Err(error) => {
+                invalid += 1;
+                println!("invalid {}: {}", run_path.display(), error);
             }
         }
-        runs.push(run);
     }
+    if invalid > 0 {
+        return Err(ReportError::InvalidRuns(invalid));
+    }
+    println!("All {} runs are valid", folders.len());
+    Ok(())
+}
 
-    let mut all_examples_cleaned = Vec::new();
-    // examples that never have screenshot are not flaky
-    for mut example in all_examples.drain() {
-        let has_screenshot = runs
-            .iter()
-            .any(|run| run.screenshots.get(&example.name).is_some());
-        let has_failures = runs.iter().any(|run| {
-            run.results
-                .get(&example.name)
-                .map(|platforms| platforms.values().any(|v| v == &Kind::Failures))
-                .unwrap_or(false)
-        });
-        if !has_screenshot && !has_failures {
-            example.flaky = false;
-        }
-        all_examples_cleaned.push(example);
+/// Run folders in `path`, from the most recent to the oldest
+fn run_folders(path: &Path) -> Result<Vec<PathBuf>, ReportError> {
+    let mut folders = loader::read_dir(path)?;
+    folders.sort();
+    folders.reverse();
+    // files like a README are not runs, and must not count towards the limit or be used
+    // as the previous run
+    folders.retain(|folder| folder.is_dir() && !loader::is_hidden(folder));
+    Ok(folders)
+}
+
+/// A factor of 1 or less would report any run above the usual duration
+fn parse_slowdown_factor(value: &str) -> Result<f32, String> {
+    let factor = value.parse::<f32>().map_err(|error| error.to_string())?;
+    if factor > 1.0 {
+        Ok(factor)
+    } else {
+        Err("must be greater than 1".to_string())
     }
+}
 
-    all_examples_cleaned.sort_by_key(|a| format!("{}/{}", a.category.0, a.name));
+/// Where commands that don't build the site put local screenshots
+fn scratch_dir() -> PathBuf {
+    std::env::temp_dir().join("example_report")
+}
 
-    template::build_site(runs, all_examples_cleaned, all_mobile_platforms)
+/// Durations of an example across all runs, for each platform it ran on.
+fn duration_history(runs: &[Run], example: &str) -> BTreeMap<String, Vec<Option<f32>>> {
+    let platforms = runs
+        .iter()
+        .filter_map(|run| run.durations.get(example))
+        .flat_map(|durations| durations.keys())
+        .collect::<HashSet<_>>();
+    platforms
+        .into_iter()
+        .map(|platform| {
+            (
+                platform.to_string(),
+                runs.iter()
+                    .map(|run| {
+                        run.durations
+                            .get(example)
+                            .and_then(|durations| durations.get(platform))
+                            .copied()
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
 }
@@ -0,0 +1,175 @@
+//! GitHub flavored Markdown summary of a run compared to a baseline, to post as a comment.
+
+use std::fmt::Write;
+
+use crate::{analysis::run_summary, Kind, Run};
+
+/// `site_url` is used to link to screenshots that are published with the site.
+pub fn summary(run: &Run, baseline: &Run, site_url: &str) -> String {
+    let site_url = format!("{}/", site_url.trim_end_matches('/'));
+    let link = |url: &str| {
+        if url.starts_with("http") {
+            url.to_string()
+        } else {
+            format!("{}{}", site_url, url)
+        }
+    };
+    let summary = run_summary::summarize(run, Some(baseline));
+    let missing = missing_screenshots(run, baseline);
+
+    let mut markdown = String::new();
+    let _ = writeln!(
+        markdown,
+        "## Example report for [`{}`](https://github.com/bevyengine/bevy/commit/{})\n",
+        run.short_commit(),
+        run.commit
+    );
+    let total = summary
+        .totals
+        .values()
+        .map(|totals| totals.successes + totals.no_screenshots + totals.failures)
+        .sum::<usize>();
+    let failures = summary.failures.len();
+    let _ = writeln!(
+        markdown,
+        "Compared to [`{}`](https://github.com/bevyengine/bevy/commit/{}): {} results, {} failures.\n",
+        baseline.short_commit(),
+        baseline.commit,
+        total,
+        failures
+    );
+
+    if summary.newly_failing.is_empty() && summary.changed.is_empty() && missing.is_empty() {
+        markdown.push_str("No new failures, changed or missing screenshots.\n");
+        return markdown;
+    }
+
+    if !summary.newly_failing.is_empty() {
+        let _ = writeln!(
+            markdown,
+            "### New failures ({})\n",
+            summary.newly_failing.len()
+        );
+        markdown.push_str("| Example | Platform |\n| --- | --- |\n");
+        for entry in &summary.newly_failing {
+            let _ = writeln!(markdown, "| {} | {} |", entry.example, entry.platform);
+        }
+        markdown.push('\n');
+    }
+
+    if !summary.changed.is_empty() {
+        let _ = writeln!(
+            markdown,
+            "### Changed screenshots ({})\n",
+            summary.changed.len()
+        );
+        markdown.push_str("| Example | Platform | Screenshot |\n| --- | --- | --- |\n");
+        for changed in &summary.changed {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} | [screenshot]({}) · [compare]({}) |",
+                changed.example,
+                changed.platform,
+                link(&changed.screenshot),
+                link(&changed.snapshot_url)
+            );
+        }
+        markdown.push('\n');
+    }
+
+    if !missing.is_empty() {
+        let _ = writeln!(markdown, "### Missing screenshots ({})\n", missing.len());
+        markdown.push_str("| Example | Platform |\n| --- | --- |\n");
+        for (example, platform) in &missing {
+            let _ = writeln!(markdown, "| {} | {} |", example, platform);
+        }
+        markdown.push('\n');
+    }
+
+    markdown
+}
+
+/// Screenshots taken in the baseline but not in the run, for examples that didn't fail
+fn missing_screenshots(run: &Run, baseline: &Run) -> Vec<(String, String)> {
+    let mut missing = baseline
+        .screenshots
+        .iter()
+        .flat_map(|(example, platforms)| platforms.keys().map(move |platform| (example, platform)))
+        .filter(|(example, platform)| {
+            let has_screenshot = run
+                .screenshots
+                .get(*example)
+                .is_some_and(|screenshots| screenshots.contains_key(*platform));
+            let failed = run
+                .results
+                .get(*example)
+                .and_then(|results| results.get(*platform))
+                == Some(&Kind::Failures);
+            !has_screenshot && !failed
+        })
+        .map(|(example, platform)| (example.clone(), platform.to_string()))
+        .collect::<Vec<_>>();
+    missing.sort();
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::test_util::{run, with_screenshot},
+        screenshot::ScreenshotState,
+        Platform,
+    };
+
+    use super::*;
+
+    #[test]
+    fn summarize_changes() {
+        let baseline = run(
+            "0123456789",
+            &[
+                ("lighting", Platform::Linux, Kind::Successes),
+                ("shadows", Platform::Linux, Kind::Successes),
+            ],
+        );
+        let baseline = with_screenshot(
+            baseline,
+            "lighting",
+            Platform::Linux,
+            ScreenshotState::Similar,
+        );
+        let baseline = with_screenshot(
+            baseline,
+            "shadows",
+            Platform::Linux,
+            ScreenshotState::Similar,
+        );
+        let current = run(
+            "abcdef0123",
+            &[
+                ("lighting", Platform::Linux, Kind::Successes),
+                ("shadows", Platform::Linux, Kind::NoScreenshots),
+            ],
+        );
+        let current = with_screenshot(
+            current,
+            "lighting",
+            Platform::Linux,
+            ScreenshotState::Changed,
+        );
+
+        let markdown = summary(&current, &baseline, "https://example.com");
+        assert!(
+            markdown.contains("[`abcdef0`](https://github.com/bevyengine/bevy/commit/abcdef0123)")
+        );
+        assert!(markdown.contains("### Changed screenshots (1)"));
+        assert!(markdown.contains(
+            "| lighting | Linux | [screenshot](https://example.com/screenshots/abcdef0123/Linux/lighting.png) · [compare](https://pixel-eagle.com/compare/abcdef0123) |"
+        ));
+        assert!(markdown.contains("### Missing screenshots (1)\n\n| Example | Platform |\n| --- | --- |\n| shadows | Linux |"));
+        assert!(!markdown.contains("New failures"));
+
+        let non_ascii = run("défaut-commit", &[]);
+        assert!(summary(&non_ascii, &baseline, "https://example.com").contains("[`défaut-`]"));
+    }
+}
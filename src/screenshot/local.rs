@@ -0,0 +1,306 @@
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ReportError, ImageUrl, SnapshotViewerUrl};
+
+use super::{ScreenshotData, ScreenshotProvider, ScreenshotSource, ScreenshotState};
+
+/// Screenshots stored as PNGs in the run folder, in `screenshots/<platform>/<category>/<name>.png`.
+///
+/// They are compared with the screenshots of the previous run, and copied with their
+/// diff masks to the site so that they can be displayed without any external service.
+pub struct Local {
+    output: PathBuf,
+}
+
+impl Local {
+    pub fn new(output: &Path) -> Self {
+        Local {
+            output: output.to_path_buf(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Comparison {
+    name: String,
+    /// URL to the screenshot, relative to the site root
+    image: String,
+    /// URL to the diff mask, if the screenshot changed
+    diff: Option<String>,
+    diff_ratio: f32,
+}
+
+impl ScreenshotProvider for Local {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn detect(&self, kind: &str) -> bool {
+        kind == "screenshots"
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    fn fetch(&self, source: &ScreenshotSource) -> Result<Option<String>, ReportError> {
+        // source is `<run>/screenshots/<platform>`
+        let (Some(platform), Some(run)) = (
+            source.path.file_name(),
+            source
+                .path
+                .parent()
+                .and_then(Path::parent)
+                .and_then(Path::file_name),
+        ) else {
+            return Ok(None);
+        };
+        let url_prefix = format!(
+            "screenshots/{}/{}",
+            run.to_string_lossy(),
+            platform.to_string_lossy()
+        );
+        let output = self.output.join(&url_prefix);
+
+        let mut comparisons = vec![];
+        for name in find_screenshots(&source.path)? {
+            let path = source.path.join(&name);
+            let image = Image::read(&path)?;
+            let previous = source
+                .previous
+                .as_ref()
+                .map(|previous| previous.join(&name))
+                .filter(|previous| previous.exists())
+                .map(|previous| Image::read(&previous))
+                .transpose()?;
+
+            let destination = output.join(&name);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(|error| ReportError::io(parent, error))?;
+            }
+            fs::copy(&path, &destination).map_err(|error| ReportError::io(&path, error))?;
+
+            let (diff_ratio, mask) = match previous {
+                Some(previous) => image.diff(&previous),
+                // new screenshot, nothing to compare to
+                None => (0.0, None),
+            };
+            let diff = if let Some(mask) = mask {
+                let diff_name = format!("{}.diff.png", name.trim_end_matches(".png"));
+                mask.write(&output.join(&diff_name))?;
+                Some(format!("{}/{}", url_prefix, diff_name))
+            } else {
+                None
+            };
+            comparisons.push(Comparison {
+                image: format!("{}/{}", url_prefix, name),
+                name,
+                diff,
+                diff_ratio,
+            });
+        }
+
+        serde_json::to_string(&comparisons)
+            .map(Some)
+            .map_err(|error| ReportError::provider(self.name(), error))
+    }
+
+    fn convert(
+        &self,
+        _source: &ScreenshotSource,
+        response: &str,
+    ) -> Result<Vec<ScreenshotData>, ReportError> {
+        let comparisons = serde_json::from_str::<Vec<Comparison>>(response)
+            .map_err(|error| ReportError::provider(self.name(), error))?;
+        Ok(comparisons
+            .into_iter()
+            .map(|comparison| ScreenshotData {
+                snapshot_url: SnapshotViewerUrl(
+                    comparison
+                        .diff
+                        .clone()
+                        .unwrap_or_else(|| comparison.image.clone()),
+                ),
+                example: comparison.name,
+                screenshot: ImageUrl(comparison.image),
+                changed: if comparison.diff_ratio > 0.0 {
+                    ScreenshotState::Changed
+                } else {
+                    ScreenshotState::Similar
+                },
+                tag: None,
+                diff_ratio: comparison.diff_ratio,
+            })
+            .collect())
+    }
+}
+
+/// Finds all PNGs in a folder, named by their path relative to the folder.
+fn find_screenshots(folder: &Path) -> Result<Vec<String>, ReportError> {
+    let mut screenshots = vec![];
+    let mut folders = vec![folder.to_path_buf()];
+    while let Some(current) = folders.pop() {
+        for entry in fs::read_dir(&current).map_err(|error| ReportError::io(&current, error))? {
+            let path = entry
+                .map_err(|error| ReportError::io(&current, error))?
+                .path();
+            if path.is_dir() {
+                folders.push(path);
+            } else if path.extension().is_some_and(|extension| extension == "png")
+                && !path.to_string_lossy().ends_with(".diff.png")
+            {
+                let name = path
+                    .strip_prefix(folder)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                screenshots.push(name);
+            }
+        }
+    }
+    screenshots.sort();
+    Ok(screenshots)
+}
+
+/// An RGBA image
+#[derive(Debug, PartialEq)]
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn read(path: &Path) -> Result<Self, ReportError> {
+        let invalid = |error: png::DecodingError| {
+            ReportError::provider("local", format!("can't decode {:?}: {}", path, error))
+        };
+        let file = File::open(path).map_err(|error| ReportError::io(path, error))?;
+        let mut decoder = png::Decoder::new(file);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(invalid)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(invalid)?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+            png::ColorType::Indexed => {
+                return Err(ReportError::provider(
+                    "local",
+                    format!("unexpected indexed colors in {:?}", path),
+                ))
+            }
+        };
+        Ok(Image {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    fn write(&self, path: &Path) -> Result<(), ReportError> {
+        let invalid = |error: png::EncodingError| {
+            ReportError::provider("local", format!("can't write {:?}: {}", path, error))
+        };
+        let file = File::create(path).map_err(|error| ReportError::io(path, error))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .map_err(invalid)?
+            .write_image_data(&self.pixels)
+            .map_err(invalid)
+    }
+
+    /// Ratio of pixels that changed from `previous`, and a mask with changed pixels
+    /// in red if there are any.
+    fn diff(&self, previous: &Image) -> (f32, Option<Image>) {
+        if self.width != previous.width || self.height != previous.height {
+            let mask = Image {
+                width: self.width,
+                height: self.height,
+                pixels: [255, 0, 0, 255].repeat(self.pixels.len() / 4),
+            };
+            return (1.0, Some(mask));
+        }
+
+        let mut changed = 0;
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for (pixel, previous) in self
+            .pixels
+            .chunks_exact(4)
+            .zip(previous.pixels.chunks_exact(4))
+        {
+            if pixel == previous {
+                pixels.extend([0, 0, 0, 0]);
+            } else {
+                changed += 1;
+                pixels.extend([255, 0, 0, 255]);
+            }
+        }
+        if changed == 0 {
+            return (0.0, None);
+        }
+        let total = (self.width * self.height).max(1);
+        (
+            changed as f32 / total as f32,
+            Some(Image {
+                width: self.width,
+                height: self.height,
+                pixels,
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(pixels: &[[u8; 4]]) -> Image {
+        Image {
+            width: pixels.len() as u32,
+            height: 1,
+            pixels: pixels.concat(),
+        }
+    }
+
+    #[test]
+    fn diff_images() {
+        let black = [0, 0, 0, 255];
+        let white = [255, 255, 255, 255];
+
+        let (ratio, mask) = image(&[black, white]).diff(&image(&[black, white]));
+        assert_eq!(ratio, 0.0);
+        assert!(mask.is_none());
+
+        let (ratio, mask) = image(&[black, white, white, white]).diff(&image(&[white; 4]));
+        assert_eq!(ratio, 0.25);
+        assert_eq!(
+            mask.unwrap().pixels,
+            [[255, 0, 0, 255], [0; 4], [0; 4], [0; 4]].concat()
+        );
+
+        let (ratio, _) = image(&[black]).diff(&image(&[black, black]));
+        assert_eq!(ratio, 1.0);
+    }
+}
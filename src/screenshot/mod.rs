@@ -1,7 +1,13 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
 use serde::{Deserialize, Serialize};
 
-use crate::{ImageUrl, SnapshotViewerUrl};
+use crate::{error::ReportError, http::HttpClient, ImageUrl, SnapshotViewerUrl};
 
+pub mod local;
 pub mod percy;
 pub mod pixeleagle;
 
@@ -23,3 +29,118 @@ pub enum ScreenshotState {
     Similar,
     Changed,
 }
+
+/// Where a provider finds screenshots for a platform in a run folder.
+///
+/// Either a `<platform>-<kind>` result file, or a `<kind>/<platform>` folder.
+pub struct ScreenshotSource {
+    pub path: PathBuf,
+    /// Content of the result file, empty for a folder
+    pub results: String,
+    /// The same source in the previous run, if it exists
+    pub previous: Option<PathBuf>,
+}
+
+/// A service storing screenshots, referenced by a [`ScreenshotSource`].
+pub trait ScreenshotProvider: Send + Sync {
+    /// Name used to enable or disable the provider from the command line
+    fn name(&self) -> &'static str;
+
+    /// Whether result files or folders of this kind are read by this provider
+    fn detect(&self, kind: &str) -> bool;
+
+    /// Fetches the screenshots described by a source, returning the raw response.
+    /// `None` if the source doesn't reference any screenshots.
+    fn fetch(&self, source: &ScreenshotSource) -> Result<Option<String>, ReportError>;
+
+    /// Converts a response from [`ScreenshotProvider::fetch`]
+    fn convert(
+        &self,
+        source: &ScreenshotSource,
+        response: &str,
+    ) -> Result<Vec<ScreenshotData>, ReportError>;
+
+    fn enabled_by_default(&self) -> bool {
+        true
+    }
+
+    /// Whether the provider needs the network, it's skipped in offline mode unless
+    /// its responses are cached
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    /// Key under which responses for this source can be cached, `None` to never cache them.
+    /// Fails when the source can't be read.
+    fn cache_key(&self, _source: &ScreenshotSource) -> Result<Option<String>, ReportError> {
+        Ok(None)
+    }
+
+    /// Whether a response won't change anymore and can be cached
+    fn is_final(&self, _response: &str) -> bool {
+        true
+    }
+}
+
+/// All known screenshot providers, and whether they are enabled.
+pub struct ScreenshotProviders {
+    providers: Vec<(Box<dyn ScreenshotProvider>, bool)>,
+}
+
+impl ScreenshotProviders {
+    /// Registers all built-in providers. Screenshots read locally are copied to `output`.
+    pub fn new(output: &Path, http: Arc<HttpClient>) -> Self {
+        let mut providers = ScreenshotProviders { providers: vec![] };
+        providers.register(pixeleagle::PixelEagle::new(http.clone()));
+        providers.register(percy::Percy::new(http));
+        providers.register(local::Local::new(output));
+        providers
+    }
+
+    pub fn register(&mut self, provider: impl ScreenshotProvider + 'static) {
+        let enabled = provider.enabled_by_default();
+        self.providers.push((Box::new(provider), enabled));
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), ReportError> {
+        let (_, provider_enabled) = self
+            .providers
+            .iter_mut()
+            .find(|(provider, _)| provider.name() == name)
+            .ok_or_else(|| ReportError::UnknownProvider(name.to_string()))?;
+        *provider_enabled = enabled;
+        Ok(())
+    }
+
+    pub fn disable_all(&mut self) {
+        for (_, enabled) in &mut self.providers {
+            *enabled = false;
+        }
+    }
+
+    /// Finds the provider for a kind of result file, and whether it's enabled
+    pub fn find(&self, kind: &str) -> Option<(&dyn ScreenshotProvider, bool)> {
+        self.providers
+            .iter()
+            .find(|(provider, _)| provider.detect(kind))
+            .map(|(provider, enabled)| (provider.as_ref(), *enabled))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enable_providers() {
+        let mut providers = ScreenshotProviders::new(Path::new("site"), Default::default());
+        assert!(matches!(providers.find("pixeleagle"), Some((_, true))));
+        assert!(matches!(providers.find("screenshots"), Some((_, true))));
+        assert!(matches!(providers.find("percy"), Some((_, false))));
+        assert!(providers.find("successes").is_none());
+
+        providers.set_enabled("percy", true).unwrap();
+        assert!(matches!(providers.find("percy"), Some((_, true))));
+        assert!(providers.set_enabled("nope", true).is_err());
+    }
+}
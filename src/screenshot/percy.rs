@@ -1,61 +1,74 @@
-use std::{thread, time::Duration};
+use std::sync::Arc;
 
 use serde::Deserialize;
 
-use crate::{ImageUrl, SnapshotViewerUrl};
+use crate::{error::ReportError, http::HttpClient, ImageUrl, SnapshotViewerUrl};
 
-use super::{ScreenshotData, ScreenshotState};
+use super::{ScreenshotData, ScreenshotProvider, ScreenshotSource, ScreenshotState};
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct Main {
     pub web_url: String,
-    pub total_comparisons: u32,
-    pub total_comparisons_diff: u32,
 }
 
-fn get_snapshots_with_retry(build_id: &str) -> SnapshotsData {
-    let mut response = ureq::get(&format!(
-        "https://percy.io/api/v1/builds/{}/snapshots",
-        build_id
-    ))
-    .call();
-    if response.is_err() {
-        thread::sleep(Duration::from_secs(20));
-        response = ureq::get(&format!(
-            "https://percy.io/api/v1/builds/{}/snapshots",
-            build_id
-        ))
-        .call();
+/// Percy is only used for mobile screenshots, and is disabled by default.
+pub struct Percy {
+    http: Arc<HttpClient>,
+}
+
+impl Percy {
+    pub fn new(http: Arc<HttpClient>) -> Self {
+        Percy { http }
     }
+}
 
-    let data = response.unwrap().into_json::<SnapshotsData>();
+impl ScreenshotProvider for Percy {
+    fn name(&self) -> &'static str {
+        "percy"
+    }
 
-    if let Ok(data) = data {
-        data
-    } else {
-        println!("error parsing Percy response: {:?}", data);
-        SnapshotsData {
-            data: vec![],
-            included: vec![],
-        }
+    fn detect(&self, kind: &str) -> bool {
+        kind == "percy"
     }
-}
 
-pub fn read_results(results: String) -> Vec<ScreenshotData> {
-    // sleep to limit how hard Percy API are used
-    thread::sleep(Duration::from_secs(1));
+    fn fetch(&self, source: &ScreenshotSource) -> Result<Option<String>, ReportError> {
+        let main = serde_json::from_str::<Main>(&source.results)
+            .map_err(|error| ReportError::provider(self.name(), error))?;
+        let build_id = main.web_url.split('/').next_back().unwrap_or_default();
+        self.http
+            .get(
+                self.name(),
+                &format!("https://percy.io/api/v1/builds/{}/snapshots", build_id),
+            )
+            .map(Some)
+    }
 
-    let Ok(main) = serde_json::from_str::<Main>(&results) else {
-        return vec![];
-    };
-    let build_id = main.web_url.split('/').last().unwrap();
-    let data = get_snapshots_with_retry(build_id);
+    fn convert(
+        &self,
+        source: &ScreenshotSource,
+        response: &str,
+    ) -> Result<Vec<ScreenshotData>, ReportError> {
+        let main = serde_json::from_str::<Main>(&source.results)
+            .map_err(|error| ReportError::provider(self.name(), error))?;
+        let data = serde_json::from_str::<SnapshotsData>(response)
+            .map_err(|error| ReportError::provider(self.name(), error))?;
+        snapshots_to_images(data, &main.web_url)
+            .map_err(|error| ReportError::provider(self.name(), error))
+    }
 
-    snapshots_to_images(data, &main.web_url)
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
 }
 
-fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<ScreenshotData> {
+/// Fails when the response references something it doesn't include
+#[allow(clippy::single_match)]
+fn snapshots_to_images(
+    snapshots: SnapshotsData,
+    build_url: &str,
+) -> Result<Vec<ScreenshotData>, String> {
+    let missing = |what: &str, id: &str| format!("{} {} is missing from the response", what, id);
     let mut images = Vec::new();
     for snapshot in snapshots.data {
         match snapshot {
@@ -84,7 +97,7 @@ fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<Screens
                             } if id == &comparison_id => Some((attributes, relationships)),
                             _ => None,
                         })
-                        .unwrap();
+                        .ok_or_else(|| missing("comparison", &comparison_id))?;
                     if let Some(comparison_tag) =
                         comparison_relationship.comparison_tag.data.as_ref()
                     {
@@ -100,7 +113,7 @@ fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<Screens
                                 }
                                 _ => None,
                             })
-                            .unwrap();
+                            .ok_or_else(|| missing("comparison tag", &comparison_tag_id))?;
                         tag = Some(format!(
                             "{} {} / {}",
                             comparison_tag.os_name, comparison_tag.os_version, comparison_tag.name
@@ -111,7 +124,9 @@ fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<Screens
                             .base_screenshot
                             .data
                             .as_ref()
-                            .unwrap()
+                            .ok_or_else(|| {
+                                missing("base screenshot of comparison", &comparison_id)
+                            })?
                             .id
                             .clone();
                         let base_screenshot = snapshots
@@ -125,8 +140,14 @@ fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<Screens
                                 }
                                 _ => None,
                             })
-                            .unwrap();
-                        base_screenshot.image.data.as_ref().unwrap().id.clone()
+                            .ok_or_else(|| missing("screenshot", &base_screenshot_id))?;
+                        base_screenshot
+                            .image
+                            .data
+                            .as_ref()
+                            .ok_or_else(|| missing("image of screenshot", &base_screenshot_id))?
+                            .id
+                            .clone()
                     } else if [
                         ReviewStateReason::UnreviewedComparisons,
                         ReviewStateReason::UserApproved,
@@ -137,7 +158,9 @@ fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<Screens
                             .head_screenshot
                             .data
                             .as_ref()
-                            .unwrap()
+                            .ok_or_else(|| {
+                                missing("head screenshot of comparison", &comparison_id)
+                            })?
                             .id
                             .clone();
                         let head_screenshot = snapshots
@@ -151,8 +174,14 @@ fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<Screens
                                 }
                                 _ => None,
                             })
-                            .unwrap();
-                        head_screenshot.image.data.as_ref().unwrap().id.clone()
+                            .ok_or_else(|| missing("screenshot", &head_screenshot_id))?;
+                        head_screenshot
+                            .image
+                            .data
+                            .as_ref()
+                            .ok_or_else(|| missing("image of screenshot", &head_screenshot_id))?
+                            .id
+                            .clone()
                     } else {
                         "".to_string()
                     };
@@ -165,7 +194,7 @@ fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<Screens
                             }
                             _ => None,
                         })
-                        .unwrap();
+                        .ok_or_else(|| missing("image", &image_id))?;
 
                     let snapshot_url = format!(
                         "{}/{}/{}",
@@ -191,7 +220,7 @@ fn snapshots_to_images(snapshots: SnapshotsData, build_url: &str) -> Vec<Screens
             _ => {}
         }
     }
-    images
+    Ok(images)
 }
 
 #[derive(Deserialize, Debug)]
@@ -329,7 +358,7 @@ mod tests {
         dbg!(read.data.len());
         dbg!(read.included.len());
         dbg!(&read.data[0]);
-        dbg!(snapshots_to_images(read, ""));
+        dbg!(snapshots_to_images(read, "").unwrap());
         // assert!(false);
     }
 
@@ -341,7 +370,7 @@ mod tests {
         dbg!(read.data.len());
         dbg!(read.included.len());
         dbg!(&read.data[0]);
-        dbg!(snapshots_to_images(read, ""));
+        dbg!(snapshots_to_images(read, "").unwrap());
         // assert!(false);
     }
 }
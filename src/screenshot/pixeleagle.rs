@@ -1,8 +1,10 @@
+use std::sync::Arc;
+
 use serde::Deserialize;
 
-use crate::{ImageUrl, SnapshotViewerUrl};
+use crate::{error::ReportError, http::HttpClient, ImageUrl, SnapshotViewerUrl};
 
-use super::{ScreenshotData, ScreenshotState};
+use super::{ScreenshotData, ScreenshotProvider, ScreenshotSource, ScreenshotState};
 
 #[derive(Deserialize)]
 struct ComparisonTarget {
@@ -37,24 +39,75 @@ enum Difference {
     Done(f32),
 }
 
-pub fn read_results(results: String) -> Vec<ScreenshotData> {
-    let Ok(target) = serde_json::from_str::<ComparisonTarget>(&results) else {
-        return vec![];
-    };
-
-    let screenshots = ureq::get(&format!(
-        "https://pixel-eagle.com/{}/runs/{}/compare/{}",
-        target.project_id, target.from, target.to
-    ))
-    .call()
-    .unwrap()
-    .into_json::<Comparison>()
-    .unwrap();
-
-    comparison_to_screenshot_data(screenshots)
+pub struct PixelEagle {
+    http: Arc<HttpClient>,
+}
+
+impl PixelEagle {
+    pub fn new(http: Arc<HttpClient>) -> Self {
+        PixelEagle { http }
+    }
+
+    /// The comparison referenced by a result file, an error if it can't be read
+    fn target(&self, source: &ScreenshotSource) -> Result<ComparisonTarget, ReportError> {
+        serde_json::from_str::<ComparisonTarget>(&source.results)
+            .map_err(|error| ReportError::provider(self.name(), error))
+    }
 }
 
-fn comparison_to_screenshot_data(comparison: Comparison) -> Vec<ScreenshotData> {
+impl ScreenshotProvider for PixelEagle {
+    fn name(&self) -> &'static str {
+        "pixeleagle"
+    }
+
+    fn detect(&self, kind: &str) -> bool {
+        kind == "pixeleagle"
+    }
+
+    fn fetch(&self, source: &ScreenshotSource) -> Result<Option<String>, ReportError> {
+        let target = self.target(source)?;
+        self.http
+            .get(
+                self.name(),
+                &format!(
+                    "https://pixel-eagle.com/{}/runs/{}/compare/{}",
+                    target.project_id, target.from, target.to
+                ),
+            )
+            .map(Some)
+    }
+
+    fn cache_key(&self, source: &ScreenshotSource) -> Result<Option<String>, ReportError> {
+        let target = self.target(source)?;
+        Ok(Some(format!(
+            "pixeleagle/{}/{}-{}",
+            target.project_id, target.from, target.to
+        )))
+    }
+
+    /// Comparisons that are still being processed will change
+    fn is_final(&self, response: &str) -> bool {
+        serde_json::from_str::<Comparison>(response).is_ok_and(|comparison| {
+            comparison
+                .diff
+                .iter()
+                .all(|screenshot| !matches!(screenshot.diff, Some(Difference::Processing)))
+        })
+    }
+
+    fn convert(
+        &self,
+        _source: &ScreenshotSource,
+        response: &str,
+    ) -> Result<Vec<ScreenshotData>, ReportError> {
+        let comparison = serde_json::from_str::<Comparison>(response)
+            .map_err(|error| ReportError::provider(self.name(), error))?;
+        comparison_to_screenshot_data(comparison)
+            .map_err(|error| ReportError::provider(self.name(), error))
+    }
+}
+
+fn comparison_to_screenshot_data(comparison: Comparison) -> Result<Vec<ScreenshotData>, String> {
     let mut result = vec![];
 
     for screenshot in comparison.new {
@@ -94,6 +147,16 @@ fn comparison_to_screenshot_data(comparison: Comparison) -> Vec<ScreenshotData>
     }
 
     for screenshot in comparison.diff {
+        let diff_ratio = match screenshot.diff {
+            Some(Difference::Done(ratio)) => ratio,
+            Some(_) => 1.0,
+            None => {
+                return Err(format!(
+                    "changed screenshot {} has no diff",
+                    screenshot.name
+                ))
+            }
+        };
         result.push(ScreenshotData {
             example: screenshot.name.clone(),
             screenshot: ImageUrl(format!(
@@ -103,20 +166,14 @@ fn comparison_to_screenshot_data(comparison: Comparison) -> Vec<ScreenshotData>
             )),
             changed: ScreenshotState::Changed,
             tag: None,
-            diff_ratio: screenshot
-                .diff
-                .map(|diff| match diff {
-                    Difference::Done(ratio) => ratio,
-                    _ => 1.0,
-                })
-                .unwrap(),
+            diff_ratio,
             snapshot_url: SnapshotViewerUrl(format!(
                 "https://pixel-eagle.com/project/{}/run/{}/compare/{}?screenshot={}",
                 comparison.project_id, comparison.from, comparison.to, screenshot.name
             )),
         });
     }
-    result
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -130,7 +187,7 @@ mod tests {
         let file = fs::read_to_string("src/screenshot/test-pixeleagle.json").unwrap();
         let read = serde_json::from_str::<Comparison>(&file).unwrap();
         // dbg!(read.diff);
-        dbg!(comparison_to_screenshot_data(read));
+        dbg!(comparison_to_screenshot_data(read).unwrap());
         // assert!(false);
     }
 }
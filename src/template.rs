@@ -1,22 +1,36 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
 
 use serde::Serialize;
-use tera::{Context, Tera};
+use tera::{Context, Tera, Value};
 
-use crate::{screenshot::ScreenshotState, Example, ImageUrl, Kind, Run, SnapshotViewerUrl};
+use crate::{
+    analysis::{log_analysis, run_summary::RunSummary, slowdown::Slowdown},
+    ansi,
+    error::{IngestionProblem, ReportError},
+    screenshot::ScreenshotState,
+    Example, ImageUrl, Kind, Run, SnapshotViewerUrl,
+};
 
 #[derive(Debug, Serialize, Default)]
 struct StringRun {
+    id: String,
     date: String,
     commit: String,
     results: HashMap<String, HashMap<String, Kind>>,
     screenshots: HashMap<String, HashMap<String, (ImageUrl, ScreenshotState, SnapshotViewerUrl)>>,
     logs: HashMap<String, HashMap<String, String>>,
+    durations: HashMap<String, HashMap<String, f32>>,
+    screenshots_unavailable: HashMap<String, String>,
 }
 
 impl From<Run> for StringRun {
     fn from(value: Run) -> Self {
         StringRun {
+            id: value.id.clone(),
             date: value.date.clone(),
             commit: value.commit.clone(),
             results: value
@@ -41,46 +55,221 @@ impl From<Run> for StringRun {
                 .collect(),
 
             logs: value.logs.clone(),
+            durations: value
+                .durations
+                .iter()
+                .map(|(k, v)| {
+                    (
+                        k.clone(),
+                        v.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+                    )
+                })
+                .collect(),
+            screenshots_unavailable: value
+                .screenshots_unavailable
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
         }
     }
 }
 
-pub fn build_site(
-    runs: Vec<Run>,
-    all_examples: Vec<Example>,
-    all_mobile_platforms: HashSet<String>,
-) {
-    let runs: Vec<StringRun> = runs.into_iter().map(|r| r.into()).collect();
-    let mut context = Context::new();
-    context.insert("runs".to_string(), &runs);
-    context.insert("all_examples".to_string(), &all_examples);
-    context.insert("all_mobile_platforms".to_string(), &all_mobile_platforms);
-
-    let mut tera = Tera::default();
-    tera.add_raw_template(
-        "icons.html",
-        &std::fs::read_to_string("./templates/icons.html").unwrap(),
-    )
-    .unwrap();
-    tera.add_raw_template(
-        "macros.html",
-        &std::fs::read_to_string("./templates/macros.html").unwrap(),
-    )
-    .unwrap();
-    tera.add_raw_template(
-        "index.html",
-        &std::fs::read_to_string("./templates/index.html").unwrap(),
-    )
-    .unwrap();
-    tera.add_raw_template(
-        "about.html",
-        &std::fs::read_to_string("./templates/about.html").unwrap(),
-    )
-    .unwrap();
-
-    let rendered = tera.render("index.html", &context).unwrap();
-    std::fs::write("./site/index.html", &rendered).unwrap();
-
-    let rendered = tera.render("about.html", &context).unwrap();
-    std::fs::write("./site/about.html", &rendered).unwrap();
+/// Templates embedded in the binary, used for the ones missing from the templates folder
+const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    ("icons.html", include_str!("../templates/icons.html")),
+    ("macros.html", include_str!("../templates/macros.html")),
+    ("index.html", include_str!("../templates/index.html")),
+    ("example.html", include_str!("../templates/example.html")),
+    ("run.html", include_str!("../templates/run.html")),
+    ("about.html", include_str!("../templates/about.html")),
+    ("feed.xml", include_str!("../templates/feed.xml")),
+];
+
+/// Everything displayed on the site.
+pub struct Report {
+    pub runs: Vec<Run>,
+    pub all_examples: Vec<Example>,
+    pub all_mobile_platforms: HashSet<String>,
+    pub all_platforms: Vec<String>,
+    pub problems: Vec<IngestionProblem>,
+    pub slowdowns: Vec<Slowdown>,
+    pub summaries: Vec<RunSummary>,
+}
+
+/// Renders the pages of the site in `output`.
+pub struct Site {
+    output: PathBuf,
+    tera: Tera,
+}
+
+impl Site {
+    /// Templates are read from `templates` when given, falling back to the embedded ones.
+    pub fn new(output: &Path, templates: Option<&Path>) -> Result<Self, ReportError> {
+        let mut tera = Tera::default();
+        tera.register_filter("ansi_to_html", ansi_to_html);
+        tera.register_filter("log_signature", log_signature);
+        for (name, default) in DEFAULT_TEMPLATES {
+            let path = templates.map(|templates| templates.join(name));
+            let content = match path.filter(|path| path.exists()) {
+                Some(path) => {
+                    fs::read_to_string(&path).map_err(|error| ReportError::io(&path, error))?
+                }
+                None => default.to_string(),
+            };
+            tera.add_raw_template(name, &content)
+                .map_err(ReportError::template)?;
+        }
+        Ok(Site {
+            output: output.to_path_buf(),
+            tera,
+        })
+    }
+
+    /// Writes `feed.xml`, with one entry per run. `site_url` is where the site is published,
+    /// as feed readers need absolute links.
+    pub fn build_feed(&self, summaries: &[RunSummary], site_url: &str) -> Result<(), ReportError> {
+        let mut context = Context::new();
+        context.insert("summaries", summaries);
+        context.insert("site_url", &format!("{}/", site_url.trim_end_matches('/')));
+        self.render("feed.xml", &context, &self.output.join("feed.xml"))
+    }
+
+    pub fn build_site(&self, report: Report) -> Result<(), ReportError> {
+        let Report {
+            runs,
+            all_examples,
+            all_mobile_platforms,
+            all_platforms,
+            problems,
+            slowdowns,
+            summaries,
+        } = report;
+        let runs: Vec<StringRun> = runs.into_iter().map(|r| r.into()).collect();
+        let mut context = Context::new();
+        context.insert("runs".to_string(), &runs);
+        context.insert("all_examples".to_string(), &all_examples);
+        context.insert("all_mobile_platforms".to_string(), &all_mobile_platforms);
+        context.insert("all_platforms".to_string(), &all_platforms);
+        context.insert("problems".to_string(), &problems);
+        context.insert("slowdowns".to_string(), &slowdowns);
+
+        self.render("index.html", &context, &self.output.join("index.html"))?;
+        self.render("about.html", &context, &self.output.join("about.html"))?;
+
+        let mut mobile_platforms = all_mobile_platforms.into_iter().collect::<Vec<_>>();
+        mobile_platforms.sort();
+        // pages are in `examples/<category>/`
+        context.insert("root", "../../");
+        for example in &all_examples {
+            let platforms = if example.category.0 == "Mobile" {
+                &mobile_platforms
+            } else {
+                &all_platforms
+            };
+            // only the platforms the example ran on, to not render empty tables
+            let platforms = platforms
+                .iter()
+                .filter(|platform| {
+                    runs.iter().any(|run| {
+                        run.results
+                            .get(&example.name)
+                            .is_some_and(|results| results.contains_key(*platform))
+                    })
+                })
+                .collect::<Vec<_>>();
+            context.insert("example", example);
+            context.insert("platforms", &platforms);
+            let path = self
+                .output
+                .join("examples")
+                .join(&example.category.0)
+                .join(format!("{}.html", example.name));
+            self.render("example.html", &context, &path)?;
+        }
+
+        // pages are in `runs/`
+        context.insert("root", "../");
+        for summary in &summaries {
+            context.insert("summary", summary);
+            let path = self
+                .output
+                .join("runs")
+                .join(format!("{}.html", summary.id));
+            self.render("run.html", &context, &path)?;
+        }
+        Ok(())
+    }
+
+    fn render(&self, template: &str, context: &Context, path: &Path) -> Result<(), ReportError> {
+        let rendered = self
+            .tera
+            .render(template, context)
+            .map_err(ReportError::template)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| ReportError::io(parent, error))?;
+        }
+        fs::write(path, rendered).map_err(|error| ReportError::io(path, error))
+    }
+}
+
+/// Colored HTML from a log with ANSI escape sequences, already escaped: use `safe` after it
+/// unless it's in an attribute
+fn ansi_to_html(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let log = tera::try_get_value!("ansi_to_html", "value", String, value);
+    Ok(Value::String(ansi::to_html(&log)))
+}
+
+/// Signature of the failure in a log, empty if nothing was found
+fn log_signature(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let log = tera::try_get_value!("log_signature", "value", String, value);
+    Ok(Value::String(
+        log_analysis::analyze(&log).signature().unwrap_or_default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::{classification, flakiness, test_util::run},
+        ExampleCategory, Platform,
+    };
+
+    use super::*;
+
+    #[test]
+    fn list_always_failing_examples() {
+        let runs = ["def456", "abc123"]
+            .into_iter()
+            .map(|commit| run(commit, &[("lighting", Platform::Linux, Kind::Failures)]))
+            .collect::<Vec<_>>();
+        let mut example = Example::new("lighting".to_string(), ExampleCategory("3d".to_string()));
+        example.flakiness = flakiness::flakiness(&runs, "lighting");
+        example.classification = classification::classify(&runs, "lighting");
+        assert_eq!(example.flakiness.score, 0.0);
+
+        let output =
+            std::env::temp_dir().join(format!("example-report-site-{}", std::process::id()));
+        let site = Site::new(&output, None).unwrap();
+        site.build_site(Report {
+            runs,
+            all_examples: vec![example],
+            all_mobile_platforms: HashSet::new(),
+            all_platforms: vec!["Linux".to_string()],
+            problems: vec![],
+            slowdowns: vec![],
+            summaries: vec![],
+        })
+        .unwrap();
+        let index = fs::read_to_string(output.join("index.html")).unwrap();
+        fs::remove_dir_all(&output).unwrap();
+
+        let row = index
+            .split("<tr class=\"example\"")
+            .nth(1)
+            .and_then(|row| row.split_once('>'))
+            .unwrap()
+            .0;
+        assert!(row.contains("data-state=\"BrokenSince\""));
+        assert!(!row.contains("display: none"));
+    }
 }
@@ -0,0 +1,231 @@
+//! Results in test report formats, as an alternative to status files.
+//!
+//! They are named `<platform>-junit.xml` for JUnit XML, and `<platform>-libtest.json` for
+//! the output of `cargo test -- -Z unstable-options --format json`. Tests are named
+//! `<category>/<name>`, `<category>::<name>`, or split in class name and name for JUnit.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{error::ReportError, ExampleCategory, Kind};
+
+/// Category of tests whose name doesn't have one
+const DEFAULT_CATEGORY: &str = "Tests";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestReportFormat {
+    JUnit,
+    Libtest,
+}
+
+impl TestReportFormat {
+    /// From the part of the file name after the platform
+    pub fn from_kind(kind: &str) -> Option<Self> {
+        match kind {
+            "junit.xml" => Some(TestReportFormat::JUnit),
+            "libtest.json" => Some(TestReportFormat::Libtest),
+            _ => None,
+        }
+    }
+
+    /// Reads all results of a report. Skipped and ignored tests didn't run and are left out.
+    pub fn parse(&self, path: &Path, content: &str) -> Result<Vec<TestResult>, ReportError> {
+        match self {
+            TestReportFormat::JUnit => parse_junit(content),
+            TestReportFormat::Libtest => parse_libtest(content),
+        }
+        .map_err(|message| ReportError::InvalidTestReport {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TestResult {
+    pub category: ExampleCategory,
+    pub name: String,
+    pub kind: Kind,
+    /// In seconds
+    pub duration: Option<f32>,
+    /// Output of failed tests
+    pub log: Option<String>,
+}
+
+fn parse_junit(content: &str) -> Result<Vec<TestResult>, String> {
+    let document = roxmltree::Document::parse(content).map_err(|error| error.to_string())?;
+    let mut results = vec![];
+    for testcase in document
+        .descendants()
+        .filter(|node| node.has_tag_name("testcase"))
+    {
+        let name = testcase
+            .attribute("name")
+            .ok_or_else(|| "test case without a name".to_string())?;
+        let (category, name) = match testcase.attribute("classname") {
+            Some(classname) if !classname.is_empty() => (
+                // class names can be module paths, like `examples.3d`
+                classname.rsplit(['.', ':']).next().unwrap_or(classname),
+                name,
+            ),
+            _ => split_name(name),
+        };
+        let child = |tag: &str| testcase.children().find(|node| node.has_tag_name(tag));
+        if child("skipped").is_some() {
+            continue;
+        }
+        let failure = child("failure").or_else(|| child("error"));
+        let log = failure.map(|failure| {
+            [
+                failure.attribute("message"),
+                failure.text(),
+                child("system-out").and_then(|node| node.text()),
+                child("system-err").and_then(|node| node.text()),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|text| !text.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+        });
+        results.push(TestResult {
+            category: ExampleCategory(category.to_string()),
+            name: name.to_string(),
+            kind: if failure.is_some() {
+                Kind::Failures
+            } else {
+                Kind::Successes
+            },
+            duration: testcase
+                .attribute("time")
+                .and_then(|time| time.parse().ok()),
+            log,
+        });
+    }
+    Ok(results)
+}
+
+#[derive(Deserialize)]
+struct LibtestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    exec_time: Option<f32>,
+    stdout: Option<String>,
+    message: Option<String>,
+}
+
+fn parse_libtest(content: &str) -> Result<Vec<TestResult>, String> {
+    let mut results = vec![];
+    for line in content.lines() {
+        // libtest output can be mixed with other output
+        if !line.trim_start().starts_with('{') {
+            continue;
+        }
+        let event = serde_json::from_str::<LibtestEvent>(line)
+            .map_err(|error| format!("{} in {:?}", error, line))?;
+        if event.kind != "test" {
+            continue;
+        }
+        let kind = match event.event.as_str() {
+            "ok" => Kind::Successes,
+            "failed" | "timeout" => Kind::Failures,
+            _ => continue,
+        };
+        let Some(name) = event.name else {
+            continue;
+        };
+        let (category, name) = split_name(&name);
+        results.push(TestResult {
+            category: ExampleCategory(category.to_string()),
+            name: name.to_string(),
+            log: (kind == Kind::Failures).then(|| {
+                [event.stdout, event.message]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }),
+            kind,
+            duration: event.exec_time,
+        });
+    }
+    Ok(results)
+}
+
+/// Splits `category/name` or `path::to::category::name`
+fn split_name(name: &str) -> (&str, &str) {
+    if let Some(split) = name.rsplit_once('/') {
+        return split;
+    }
+    match name.rsplit_once("::") {
+        Some((path, name)) => (path.rsplit("::").next().unwrap_or(path), name),
+        None => (DEFAULT_CATEGORY, name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_junit() {
+        let report = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="Linux">
+    <testcase classname="3d" name="lighting" time="1.5" />
+    <testcase classname="examples.2d" name="sprite">
+      <failure message="panicked">at src/main.rs</failure>
+    </testcase>
+    <testcase name="ui/button"><skipped /></testcase>
+  </testsuite>
+</testsuites>"#;
+        let results = TestReportFormat::JUnit
+            .parse(Path::new("Linux-junit.xml"), report)
+            .unwrap();
+        assert_eq!(
+            results,
+            [
+                TestResult {
+                    category: ExampleCategory("3d".to_string()),
+                    name: "lighting".to_string(),
+                    kind: Kind::Successes,
+                    duration: Some(1.5),
+                    log: None,
+                },
+                TestResult {
+                    category: ExampleCategory("2d".to_string()),
+                    name: "sprite".to_string(),
+                    kind: Kind::Failures,
+                    duration: None,
+                    log: Some("panicked\nat src/main.rs".to_string()),
+                },
+            ]
+        );
+        assert!(TestReportFormat::JUnit
+            .parse(Path::new("Linux-junit.xml"), "<testsuite>")
+            .is_err());
+    }
+
+    #[test]
+    fn read_libtest() {
+        let report = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "examples::3d::lighting" }
+{ "type": "test", "name": "examples::3d::lighting", "event": "ok", "exec_time": 0.5 }
+{ "type": "test", "name": "2d/sprite", "event": "failed", "stdout": "thread panicked" }
+{ "type": "test", "name": "button", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1 }"#;
+        let results = TestReportFormat::Libtest
+            .parse(Path::new("Linux-libtest.json"), report)
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].category.0, "3d");
+        assert_eq!(results[0].name, "lighting");
+        assert_eq!(results[0].duration, Some(0.5));
+        assert_eq!(results[1].kind, Kind::Failures);
+        assert_eq!(results[1].log.as_deref(), Some("thread panicked"));
+        assert_eq!(split_name("button"), ("Tests", "button"));
+    }
+}
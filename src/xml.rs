@@ -0,0 +1,32 @@
+//! Helpers for the SVG and XML files written next to the site.
+
+/// File name for a platform, as they can contain spaces and slashes: `WebGL2 / firefox`
+/// becomes `webgl2-firefox`.
+pub fn slug(platform: &str) -> String {
+    platform
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Escapes `text` for XML content and attributes.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn platform_slugs() {
+        assert_eq!(slug("Linux"), "linux");
+        assert_eq!(slug("WebGL2 / firefox"), "webgl2-firefox");
+        assert_eq!(slug("Android 14 / Pixel 8"), "android-14-pixel-8");
+    }
+}